@@ -2,13 +2,37 @@ use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use flumedb::OffsetLog;
 use ssb_legacy_msg_data::json;
 use ssb_validate::message::{
-    par_validate_message_hash_chain_of_feed, validate_message_hash_chain, SsbMessage,
+    par_validate_first_messages, par_validate_message_hash_chain_of_feed,
+    validate_message_hash_chain, SsbMessage,
 };
 use ssb_validate::message_value::{
     par_validate_message_value, par_validate_message_value_hash_chain_of_feed,
     validate_message_value, validate_message_value_hash_chain,
 };
 
+/// Benchmark batch validation of first messages (multi-threaded).
+///
+/// The test vector holds a single feed, so its first message stands in for the first messages of
+/// many distinct feeds.
+pub fn par_validate_first_messages_bench(c: &mut Criterion) {
+    let in_log = OffsetLog::<u32>::open_read_only("./test_vecs/piet.offset").unwrap();
+
+    let msg = in_log
+        .iter()
+        .map(|entry| entry.data)
+        .take(1)
+        .collect::<Vec<_>>();
+
+    let msgs = vec![msg[0].clone(); 1000];
+
+    c.bench_function("par_validate_first_messages", |b| {
+        b.iter(|| {
+            let res = par_validate_first_messages(black_box(&msgs[..]));
+            assert!(res.is_ok());
+        })
+    });
+}
+
 /// Benchmark validation of a single message value in isolation (single-threaded).
 pub fn validate_message_value_bench(c: &mut Criterion) {
     let in_log = OffsetLog::<u32>::open_read_only("./test_vecs/piet.offset").unwrap();
@@ -19,7 +43,7 @@ pub fn validate_message_value_bench(c: &mut Criterion) {
         .take(1)
         .collect::<Vec<_>>();
 
-    let message = json::from_slice::<SsbMessage>(&msg[0].as_ref()).unwrap();
+    let message = json::from_slice::<SsbMessage>(msg[0].as_ref()).unwrap();
     let message_value = message.value;
     let value_bytes = json::to_vec(&message_value, false).unwrap();
 
@@ -43,7 +67,7 @@ pub fn par_validate_message_value_bench(c: &mut Criterion) {
 
     let mut msg_value_bytes = Vec::new();
     for msg in msgs {
-        let message = json::from_slice::<SsbMessage>(&msg.as_ref()).unwrap();
+        let message = json::from_slice::<SsbMessage>(msg.as_ref()).unwrap();
         let message_value = message.value;
         let value_bytes = json::to_vec(&message_value, false).unwrap();
         msg_value_bytes.push(value_bytes);
@@ -69,7 +93,7 @@ pub fn validate_message_value_hash_chain_bench(c: &mut Criterion) {
 
     let mut msg_value_bytes = Vec::new();
     for msg in msgs {
-        let message = json::from_slice::<SsbMessage>(&msg.as_ref()).unwrap();
+        let message = json::from_slice::<SsbMessage>(msg.as_ref()).unwrap();
         let message_value = message.value;
         let value_bytes = json::to_vec(&message_value, false).unwrap();
         msg_value_bytes.push(value_bytes);
@@ -98,7 +122,7 @@ pub fn par_validate_message_value_hash_chain_bench(c: &mut Criterion) {
 
     let mut msg_value_bytes = Vec::new();
     for msg in msgs {
-        let message = json::from_slice::<SsbMessage>(&msg.as_ref()).unwrap();
+        let message = json::from_slice::<SsbMessage>(msg.as_ref()).unwrap();
         let message_value = message.value;
         let value_bytes = json::to_vec(&message_value, false).unwrap();
         msg_value_bytes.push(value_bytes);
@@ -158,6 +182,11 @@ criterion_group! {
     config = Criterion::default().sample_size(10);
     targets = par_validate_messages_bench
 }
+criterion_group! {
+    name = par_validate_first;
+    config = Criterion::default().sample_size(10);
+    targets = par_validate_first_messages_bench
+}
 criterion_group!(validate_single_value, validate_message_value_bench);
 criterion_group! {
     name = par_validate_single_value;
@@ -176,6 +205,7 @@ criterion_group! {
 criterion_main!(
     validate_single,
     par_validate_batch,
+    par_validate_first,
     validate_single_value,
     par_validate_single_value,
     validate_value_chain,
@@ -0,0 +1,330 @@
+//! Functions for validating [Bendy Butt](https://github.com/ssb-ngi-pointer/bendy-butt-spec)
+//! (`bbfeed-v1`) messages, the feed format used by metafeeds.
+//!
+//! A Bendy Butt message is a [bencoded](https://en.wikipedia.org/wiki/Bencode) list of two
+//! elements: `[payload, signature]`. The payload is itself a list of five elements: `[author,
+//! sequence, previous, timestamp, contentSection]`. Feed ids, message ids and signatures are
+//! stored using the [binary field encoding](https://github.com/ssb-ngi-pointer/ssb-bfe-spec)
+//! (BFE), meaning each is prefixed by a type byte and a format byte.
+//!
+//! The key of a message is the `sha256` hash of the complete bencoded message (payload and
+//! signature). It is this hash which must be referenced by the `previous` field of the next
+//! message in the feed.
+//!
+//! As with the classic JSON feed format, this module does not perform signature verification.
+use sha2::{Digest, Sha256};
+use snafu::ensure;
+
+use crate::error::{InvalidBendyButtMessage, Result};
+use crate::utils::{self, BinaryMessage};
+
+/// Maximum length of an encoded message, in bytes.
+const MAX_MESSAGE_LENGTH: usize = 8192;
+/// Maximum nesting depth of lists and dictionaries accepted by the bencode decoder.
+const MAX_NESTING_DEPTH: usize = 32;
+
+/// BFE type-format prefix of a Bendy Butt feed id.
+const BFE_BENDY_BUTT_FEED: [u8; 2] = [0x00, 0x03];
+/// BFE type-format prefix of a Bendy Butt message id.
+const BFE_BENDY_BUTT_MESSAGE: [u8; 2] = [0x01, 0x04];
+/// BFE type-format prefix of an ed25519 signature.
+const BFE_SIGNATURE: [u8; 2] = [0x04, 0x00];
+/// BFE type-format prefix of box1 encrypted content.
+const BFE_BOX: [u8; 2] = [0x05, 0x00];
+/// BFE type-format prefix of box2 encrypted content.
+const BFE_BOX2: [u8; 2] = [0x05, 0x01];
+/// BFE type-format prefix of the nil value (used for the `previous` of the first message).
+const BFE_NIL: [u8; 2] = [0x06, 0x02];
+
+/// A decoded bencode value, borrowing byte strings from the input.
+enum Bencode<'a> {
+    Integer(i64),
+    Bytes(&'a [u8]),
+    List(Vec<Bencode<'a>>),
+    Dict(Vec<(&'a [u8], Bencode<'a>)>),
+}
+
+/// Validate a single Bendy Butt message (in isolation).
+///
+/// This checks that:
+/// - the message is canonical bencode and does not exceed 8192 bytes
+/// - the message is a `[payload, signature]` pair and the payload has five elements
+/// - the author is a Bendy Butt feed id and the signature is an ed25519 signature
+/// - the content section is either a `[content, contentSignature]` pair (where the content is a
+///   dictionary with a `type`) or encrypted content
+/// - the sequence is 1 and the previous is nil if the message claims to be the first message,
+///   or that the sequence is greater than 1 and the previous is a Bendy Butt message id otherwise
+///
+/// This does not check:
+/// - the signature or the content signature
+/// - anything to do with the actual previous message
+pub fn validate_bendy_butt_message<T: AsRef<[u8]>>(message_bytes: T) -> Result<()> {
+    let message_bytes = message_bytes.as_ref();
+    let payload = decode_message(message_bytes)?;
+
+    ensure!(
+        (payload.sequence == 1) == payload.previous.is_none(),
+        InvalidBendyButtMessage {
+            message: message_bytes.to_owned(),
+            reason: "sequence and previous are inconsistent",
+        }
+    );
+
+    Ok(())
+}
+
+/// Validate a Bendy Butt message in relation to the previous message.
+///
+/// This performs the same structural checks as [`validate_bendy_butt_message`], plus:
+/// - the sequence starts at one and the previous is nil if it's the first message
+/// - the sequence increments correctly
+/// - the author has not changed
+/// - the previous matches the key (`sha256` hash) of the previous message, otherwise the feed is
+///   forked
+///
+/// `previous_msg_bytes` will be `None` only when `message_bytes` is the first message by that author.
+pub fn validate_bendy_butt_message_hash_chain<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+    message_bytes: T,
+    previous_msg_bytes: Option<U>,
+) -> Result<()> {
+    let message_bytes = message_bytes.as_ref();
+    let message = decode_message(message_bytes)?;
+
+    let previous = match previous_msg_bytes {
+        Some(previous_bytes) => Some(decode_message(previous_bytes.as_ref())?),
+        None => None,
+    };
+
+    utils::check_binary_continuity(&message, previous.as_ref(), message_bytes, feed_id_string)
+}
+
+/// Decode a message and check its structure, mapping any problem to
+/// `Error::InvalidBendyButtMessage`.
+fn decode_message(message_bytes: &[u8]) -> Result<BinaryMessage> {
+    match parse_message(message_bytes) {
+        Ok(payload) => Ok(payload),
+        Err(reason) => InvalidBendyButtMessage {
+            message: message_bytes.to_owned(),
+            reason,
+        }
+        .fail(),
+    }
+}
+
+/// Parse the structure of a message, returning a description of the first problem found.
+fn parse_message(message_bytes: &[u8]) -> std::result::Result<BinaryMessage, &'static str> {
+    if message_bytes.len() > MAX_MESSAGE_LENGTH {
+        return Err("message must not be longer than 8192 bytes");
+    }
+
+    let message = decode(message_bytes).ok_or("message is not canonical bencode")?;
+    let (payload, signature) = match message {
+        Bencode::List(ref items) if items.len() == 2 => (&items[0], &items[1]),
+        _ => return Err("message must be a list of payload and signature"),
+    };
+    let fields = match payload {
+        Bencode::List(items) if items.len() == 5 => items,
+        _ => return Err("payload must be a list of five elements"),
+    };
+
+    let author = bfe_data(&fields[0], BFE_BENDY_BUTT_FEED, 32).ok_or("invalid author")?;
+
+    let sequence = match fields[1] {
+        Bencode::Integer(sequence) if sequence >= 1 => sequence as u64,
+        _ => return Err("sequence must be a positive integer"),
+    };
+
+    let previous = match fields[2] {
+        Bencode::Bytes(bytes) if bytes == BFE_NIL => None,
+        ref previous => {
+            Some(bfe_data(previous, BFE_BENDY_BUTT_MESSAGE, 32).ok_or("invalid previous")?)
+        }
+    };
+
+    match fields[3] {
+        Bencode::Integer(timestamp) if timestamp >= 0 => (),
+        _ => return Err("timestamp must be a non-negative integer"),
+    }
+
+    match fields[4] {
+        Bencode::List(ref section) if section.len() == 2 => {
+            match section[0] {
+                Bencode::Dict(ref entries) if entries.iter().any(|(key, _)| *key == b"type") => (),
+                _ => return Err("content must be a dictionary with a type"),
+            }
+            bfe_data(&section[1], BFE_SIGNATURE, 64).ok_or("invalid content signature")?;
+        }
+        Bencode::Bytes(bytes) if bytes.starts_with(&BFE_BOX) || bytes.starts_with(&BFE_BOX2) => (),
+        _ => return Err("content section must be content and signature or encrypted content"),
+    }
+
+    bfe_data(signature, BFE_SIGNATURE, 64).ok_or("invalid signature")?;
+
+    let mut author_key = [0u8; 32];
+    author_key.copy_from_slice(author);
+
+    Ok(BinaryMessage {
+        author: author_key,
+        sequence,
+        previous: previous.map(|previous| {
+            let mut previous_key = [0u8; 32];
+            previous_key.copy_from_slice(previous);
+            previous_key
+        }),
+        key: Sha256::digest(message_bytes).into(),
+    })
+}
+
+/// Return the data of a BFE-encoded byte string, if it has the given type-format prefix and
+/// data length.
+fn bfe_data<'a>(value: &Bencode<'a>, prefix: [u8; 2], len: usize) -> Option<&'a [u8]> {
+    match *value {
+        Bencode::Bytes(bytes) if bytes.len() == len + 2 && bytes.starts_with(&prefix) => {
+            Some(&bytes[2..])
+        }
+        _ => None,
+    }
+}
+
+/// Format a Bendy Butt feed id for use in error messages.
+fn feed_id_string(author: &[u8; 32]) -> String {
+    format!("@{}.bbfeed-v1", base64::encode(author))
+}
+
+/// Decode a complete bencode value, rejecting trailing data and non-canonical encodings.
+fn decode(bytes: &[u8]) -> Option<Bencode<'_>> {
+    match decode_partial(bytes, 0)? {
+        (value, []) => Some(value),
+        _ => None,
+    }
+}
+
+/// Decode a single bencode value from the start of `bytes`, returning it along with the
+/// remaining input.
+fn decode_partial(bytes: &[u8], depth: usize) -> Option<(Bencode<'_>, &[u8])> {
+    if depth > MAX_NESTING_DEPTH {
+        return None;
+    }
+
+    match *bytes.first()? {
+        b'i' => {
+            let end = bytes.iter().position(|&byte| byte == b'e')?;
+            let integer = parse_integer(&bytes[1..end])?;
+            Some((Bencode::Integer(integer), &bytes[end + 1..]))
+        }
+        b'l' => {
+            let mut items = Vec::new();
+            let mut rest = &bytes[1..];
+            while *rest.first()? != b'e' {
+                let (item, tail) = decode_partial(rest, depth + 1)?;
+                items.push(item);
+                rest = tail;
+            }
+            Some((Bencode::List(items), &rest[1..]))
+        }
+        b'd' => {
+            let mut entries: Vec<(&[u8], Bencode)> = Vec::new();
+            let mut rest = &bytes[1..];
+            while *rest.first()? != b'e' {
+                let (key, tail) = decode_byte_string(rest)?;
+                // Keys must be unique and sorted.
+                if let Some((previous_key, _)) = entries.last() {
+                    if key <= *previous_key {
+                        return None;
+                    }
+                }
+                let (value, tail) = decode_partial(tail, depth + 1)?;
+                entries.push((key, value));
+                rest = tail;
+            }
+            Some((Bencode::Dict(entries), &rest[1..]))
+        }
+        b'0'..=b'9' => {
+            let (string, rest) = decode_byte_string(bytes)?;
+            Some((Bencode::Bytes(string), rest))
+        }
+        _ => None,
+    }
+}
+
+/// Decode a length-prefixed byte string from the start of `bytes`.
+fn decode_byte_string(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let colon = bytes.iter().position(|&byte| byte == b':')?;
+    let len = parse_integer(&bytes[..colon])?;
+    if len < 0 {
+        return None;
+    }
+    let start = colon + 1;
+    let end = start.checked_add(len as usize)?;
+    if end > bytes.len() {
+        return None;
+    }
+    Some((&bytes[start..end], &bytes[end..]))
+}
+
+/// Parse a canonical decimal integer (no leading zeros, no negative zero).
+fn parse_integer(digits: &[u8]) -> Option<i64> {
+    let unsigned = digits.strip_prefix(b"-").unwrap_or(digits);
+    let canonical = match unsigned {
+        [] => false,
+        [b'0'] => unsigned.len() == digits.len(),
+        [first, ..] => *first != b'0' && unsigned.iter().all(u8::is_ascii_digit),
+    };
+    if !canonical {
+        return None;
+    }
+    std::str::from_utf8(digits).ok()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bendy_butt::{validate_bendy_butt_message, validate_bendy_butt_message_hash_chain};
+    use crate::error::Error;
+    use crate::test_data::{BENDY_BUTT_MESSAGE_1, BENDY_BUTT_MESSAGE_2};
+
+    #[test]
+    fn it_validates_a_bendy_butt_message() {
+        assert!(validate_bendy_butt_message(BENDY_BUTT_MESSAGE_1).is_ok());
+        assert!(validate_bendy_butt_message(BENDY_BUTT_MESSAGE_2).is_ok());
+    }
+
+    #[test]
+    fn it_validates_a_bendy_butt_hash_chain() {
+        assert!(
+            validate_bendy_butt_message_hash_chain::<_, &[u8]>(BENDY_BUTT_MESSAGE_1, None).is_ok()
+        );
+        assert!(validate_bendy_butt_message_hash_chain(
+            BENDY_BUTT_MESSAGE_2,
+            Some(BENDY_BUTT_MESSAGE_1)
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn it_detects_incorrect_bendy_butt_sequence() {
+        let result = validate_bendy_butt_message_hash_chain(
+            BENDY_BUTT_MESSAGE_2,
+            Some(BENDY_BUTT_MESSAGE_2),
+        );
+        match result {
+            Err(Error::InvalidSequenceNumber {
+                message: _,
+                actual: 2,
+                expected: 3,
+            }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_detects_invalid_bencode() {
+        let truncated = &BENDY_BUTT_MESSAGE_1[..BENDY_BUTT_MESSAGE_1.len() - 1];
+        match validate_bendy_butt_message(truncated) {
+            Err(Error::InvalidBendyButtMessage {
+                message: _,
+                reason: _,
+            }) => {}
+            _ => panic!(),
+        }
+    }
+}
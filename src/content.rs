@@ -0,0 +1,278 @@
+//! Typed representations of the `content` of common message types.
+//!
+//! The feed format places no constraints on the `content` of a public message beyond it being an
+//! object with a `type`. The types here cover the message kinds which most clients handle; any
+//! other content (including content of a known type which does not have the expected fields) is
+//! kept as [`KnownContent::Other`], so that classification never rejects a valid message.
+use serde::{de::DeserializeOwned, Deserialize};
+use ssb_legacy_msg_data::{
+    json::{from_slice, to_vec},
+    value::Value,
+};
+use ssb_multiformats::{multifeed::Multifeed, multihash::Multihash};
+
+use crate::error::Result;
+use crate::message_value::{validate_message_value_parsed, SsbMessageValue};
+
+/// The content of a `post` message.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Post {
+    pub text: String,
+    /// The first message of the thread, if this post is a reply.
+    pub root: Option<String>,
+}
+
+/// The content of a `contact` message.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Contact {
+    /// The feed which is (un)followed or (un)blocked.
+    pub contact: String,
+    pub following: Option<bool>,
+    pub blocking: Option<bool>,
+}
+
+/// The `vote` of a `vote` message.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Vote {
+    /// The message which is voted on.
+    pub link: String,
+    pub value: f64,
+    pub expression: Option<String>,
+}
+
+/// The content of a `vote` message.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+struct VoteContent {
+    vote: Vote,
+}
+
+/// The content of an `about` message.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct About {
+    /// The feed or message which is described.
+    pub about: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+/// The `content` of a message, classified by its `type`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KnownContent {
+    Post(Post),
+    Contact(Contact),
+    Vote(Vote),
+    About(About),
+    /// Content of any other type, encrypted content, or content of a known type which does not
+    /// have the expected fields.
+    Other(Value),
+}
+
+impl KnownContent {
+    /// Classify the `content` of a message by its `type`.
+    pub fn classify(content: &Value) -> KnownContent {
+        let content_type = match content {
+            Value::Object(object) => match object.get("type") {
+                Some(Value::String(content_type)) => content_type.as_str(),
+                _ => "",
+            },
+            _ => "",
+        };
+
+        let known = match content_type {
+            "post" => parse(content).map(KnownContent::Post),
+            "contact" => parse(content).map(KnownContent::Contact),
+            "vote" => parse::<VoteContent>(content).map(|content| KnownContent::Vote(content.vote)),
+            "about" => parse(content).map(KnownContent::About),
+            _ => None,
+        };
+
+        known.unwrap_or_else(|| KnownContent::Other(content.clone()))
+    }
+}
+
+/// Deserialize content into a typed representation, if it has the expected fields.
+fn parse<T: DeserializeOwned>(content: &Value) -> Option<T> {
+    let bytes = to_vec(content, false).ok()?;
+    from_slice::<T>(&bytes).ok()
+}
+
+/// Validate a single message value (in isolation) and classify its `content`.
+///
+/// This performs exactly the same checks as
+/// [`validate_message_value`](crate::message_value::validate_message_value). On success, the
+/// parsed message value is returned along with its classified content.
+///
+/// # Example
+///```
+///use ssb_validate::content::{validate_and_classify, KnownContent};
+///use ssb_validate::test_data::MESSAGE_VALUE_1;
+///let (_, content) = validate_and_classify(MESSAGE_VALUE_1).unwrap();
+///match content {
+///    KnownContent::About(about) => assert_eq!(about.name.as_deref(), Some("Piet")),
+///    _ => panic!(),
+///}
+///```
+pub fn validate_and_classify<T: AsRef<[u8]>>(
+    message_bytes: T,
+) -> Result<(SsbMessageValue, KnownContent)> {
+    let message_value = validate_message_value_parsed(message_bytes)?;
+    let content = KnownContent::classify(&message_value.content.0);
+
+    Ok((message_value, content))
+}
+
+/// An SSB reference found in the `content` of a message, classified by what it refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Link {
+    /// A feed (`@...`).
+    Feed(Multifeed),
+    /// A message (`%...`).
+    Message(Multihash),
+    /// A blob (`&...`).
+    Blob(Multihash),
+}
+
+impl Link {
+    /// Parse the given string as a reference in the legacy encoding, if it is one.
+    pub fn from_legacy(link: &str) -> Option<Link> {
+        if link.starts_with('@') {
+            match Multifeed::from_legacy(link.as_bytes()) {
+                Ok((feed, [])) => Some(Link::Feed(feed)),
+                _ => None,
+            }
+        } else {
+            match Multihash::from_legacy(link.as_bytes()) {
+                Ok((hash @ Multihash::Message(_), [])) => Some(Link::Message(hash)),
+                Ok((hash @ Multihash::Blob(_), [])) => Some(Link::Blob(hash)),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Collect every reference in the given `content`, in the order they appear.
+///
+/// Every string value (at any depth) which is a valid reference is collected, whatever field it
+/// is found in; object keys, and references embedded in longer strings (such as the `text` of a
+/// post), are not. Encrypted content has no references.
+pub fn collect_links(content: &Value) -> Vec<Link> {
+    let mut links = Vec::new();
+    collect_links_into(content, &mut links);
+    links
+}
+
+fn collect_links_into(value: &Value, links: &mut Vec<Link>) {
+    match value {
+        Value::String(string) => links.extend(Link::from_legacy(string)),
+        Value::Object(object) => object
+            .iter()
+            .for_each(|(_, value)| collect_links_into(value, links)),
+        Value::Array(array) => array
+            .iter()
+            .for_each(|value| collect_links_into(value, links)),
+        _ => {}
+    }
+}
+
+/// Validate a single message value (in isolation) and collect the references in its `content`.
+///
+/// This performs exactly the same checks as
+/// [`validate_message_value`](crate::message_value::validate_message_value). On success, the
+/// parsed message value is returned along with the references collected by [`collect_links`].
+/// This is useful for building an index of links while validating.
+///
+/// # Example
+///```
+///use ssb_validate::content::{validate_and_collect_links, Link};
+///use ssb_validate::test_data::MESSAGE_VALUE_1;
+///let (_, links) = validate_and_collect_links(MESSAGE_VALUE_1).unwrap();
+///assert!(matches!(links[..], [Link::Feed(_)]));
+///```
+pub fn validate_and_collect_links<T: AsRef<[u8]>>(
+    message_bytes: T,
+) -> Result<(SsbMessageValue, Vec<Link>)> {
+    let message_value = validate_message_value_parsed(message_bytes)?;
+    let links = collect_links(&message_value.content.0);
+
+    Ok((message_value, links))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_data::{MESSAGE_VALUE_1, MESSAGE_VALUE_2};
+
+    #[test]
+    fn it_classifies_about_content() {
+        let (message_value, content) = validate_and_classify(MESSAGE_VALUE_1).unwrap();
+        assert_eq!(message_value.sequence, 1);
+        assert_eq!(
+            content,
+            KnownContent::About(About {
+                about: "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519".to_string(),
+                name: Some("Piet".to_string()),
+                description: None,
+            })
+        );
+    }
+
+    #[test]
+    fn it_classifies_vote_content() {
+        let content: Value = from_slice(
+            br#"{"type":"vote","vote":{"link":"%/v5mCnV/kmnVtnF3zXtD4tbzoEQo4kRq/0d/bgxP1WI=.sha256","value":1,"expression":"Like"}}"#,
+        )
+        .unwrap();
+        match KnownContent::classify(&content) {
+            KnownContent::Vote(vote) => {
+                assert_eq!(vote.value, 1.0);
+                assert_eq!(vote.expression.as_deref(), Some("Like"));
+            }
+            other => panic!("unexpected content: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn it_keeps_unknown_or_incomplete_content_as_other() {
+        let unknown: Value = from_slice(br#"{"type":"pub","address":"example.com"}"#).unwrap();
+        assert_eq!(
+            KnownContent::classify(&unknown),
+            KnownContent::Other(unknown.clone())
+        );
+
+        let post_without_text: Value = from_slice(br#"{"type":"post"}"#).unwrap();
+        assert_eq!(
+            KnownContent::classify(&post_without_text),
+            KnownContent::Other(post_without_text.clone())
+        );
+
+        let private = Value::String("AAAA.box".to_string());
+        assert_eq!(
+            KnownContent::classify(&private),
+            KnownContent::Other(private.clone())
+        );
+    }
+
+    #[test]
+    fn it_collects_the_links_of_content() {
+        let (_, links) = validate_and_collect_links(MESSAGE_VALUE_2).unwrap();
+        assert_eq!(
+            links,
+            vec![
+                Link::from_legacy("@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519").unwrap(),
+                Link::from_legacy("&MxwsfZoq7X6oqnEX/TWIlAqd6S+jsUA6T1hqZYdl7RM=.sha256").unwrap(),
+            ]
+        );
+        assert!(matches!(links[1], Link::Blob(_)));
+
+        let content: Value = from_slice(
+            br#"{"type":"post","text":"hi @U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519","root":"%/v5mCnV/kmnVtnF3zXtD4tbzoEQo4kRq/0d/bgxP1WI=.sha256","mentions":[{"link":"&notahash"}]}"#,
+        )
+        .unwrap();
+        match &collect_links(&content)[..] {
+            [Link::Message(_)] => {}
+            other => panic!("unexpected links: {:?}", other),
+        }
+
+        assert!(collect_links(&Value::String("AAAA.box".to_string())).is_empty());
+    }
+}
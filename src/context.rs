@@ -0,0 +1,139 @@
+//! A reusable validation context for hot loops.
+//!
+//! The regular expressions used to check field order and canonical base64 are compiled lazily,
+//! the first time they are needed. Constructing a [`ValidatorContext`] compiles them up front, so
+//! the cost is not paid inside the loop, and holds the [`ValidationOptions`] to apply to every
+//! message validated through it.
+use crate::error::Result;
+use crate::message::validate_message_hash_chain_with_options;
+use crate::message_value::{
+    validate_message_value_hash_chain_with_options, validate_message_value_with_options,
+};
+use crate::options::ValidationOptions;
+use crate::utils::{
+    CANONICAL_BASE64_BOX2_RE, CANONICAL_BASE64_RE, LEGACY_JS_ORDER_RE, MESSAGE_VALUE_ORDER_RE,
+};
+
+/// Preconfigured validator which delegates to the functions of [`message`](crate::message) and
+/// [`message_value`](crate::message_value).
+///
+/// # Example
+///```
+///use ssb_validate::context::ValidatorContext;
+///use ssb_validate::options::ValidationOptions;
+///let valid_message_1 = r##"{
+///  "previous": null,
+///  "author": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+///  "sequence": 1,
+///  "timestamp": 1470186877575,
+///  "hash": "sha256",
+///  "content": {
+///    "type": "about",
+///    "about": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+///    "name": "Piet"
+///  },
+///  "signature": "QJKWui3oyK6r5dH13xHkEVFhfMZDTXfK2tW21nyfheFClSf69yYK77Itj1BGcOimZ16pj9u3tMArLUCGSscqCQ==.sig.ed25519"
+///}"##;
+/// let ctx = ValidatorContext::with_options(ValidationOptions {
+///     max_value_length: 4096,
+///     ..Default::default()
+/// });
+/// assert!(ctx.validate_message_value(valid_message_1.as_bytes()).is_ok());
+///```
+#[derive(Debug, Clone)]
+pub struct ValidatorContext {
+    options: ValidationOptions,
+}
+
+impl ValidatorContext {
+    /// Create a context which performs the default checks.
+    pub fn new() -> ValidatorContext {
+        ValidatorContext::with_options(ValidationOptions::default())
+    }
+
+    /// Create a context which performs the optional checks enabled in `options`.
+    pub fn with_options(options: ValidationOptions) -> ValidatorContext {
+        lazy_static::initialize(&CANONICAL_BASE64_RE);
+        lazy_static::initialize(&CANONICAL_BASE64_BOX2_RE);
+        lazy_static::initialize(&MESSAGE_VALUE_ORDER_RE);
+        lazy_static::initialize(&LEGACY_JS_ORDER_RE);
+
+        ValidatorContext { options }
+    }
+
+    /// The options applied by this context.
+    pub fn options(&self) -> &ValidationOptions {
+        &self.options
+    }
+
+    /// Validate a single message value (in isolation).
+    ///
+    /// See [`validate_message_value`](crate::message_value::validate_message_value).
+    pub fn validate_message_value<T: AsRef<[u8]>>(&self, message_bytes: T) -> Result<()> {
+        validate_message_value_with_options(message_bytes, &self.options)
+    }
+
+    /// Validate a message value in relation to the previous message value.
+    ///
+    /// See [`validate_message_value_hash_chain`](crate::message_value::validate_message_value_hash_chain).
+    pub fn validate_message_value_hash_chain<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+        &self,
+        message_bytes: T,
+        previous_msg_bytes: Option<U>,
+    ) -> Result<()> {
+        validate_message_value_hash_chain_with_options(
+            message_bytes,
+            previous_msg_bytes,
+            &self.options,
+        )
+    }
+
+    /// Validate a message in relation to the previous message.
+    ///
+    /// See [`validate_message_hash_chain`](crate::message::validate_message_hash_chain).
+    pub fn validate_message_hash_chain<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+        &self,
+        message_bytes: T,
+        previous_msg_bytes: Option<U>,
+    ) -> Result<()> {
+        validate_message_hash_chain_with_options(message_bytes, previous_msg_bytes, &self.options)
+    }
+}
+
+impl Default for ValidatorContext {
+    fn default() -> Self {
+        ValidatorContext::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::ValidatorContext;
+    use crate::error::Error;
+    use crate::options::ValidationOptions;
+    use crate::test_data::{MESSAGE_1, MESSAGE_2, MESSAGE_VALUE_1, MESSAGE_VALUE_2};
+
+    #[test]
+    fn it_validates_with_the_default_options() {
+        let ctx = ValidatorContext::new();
+        assert!(ctx.validate_message_value(MESSAGE_VALUE_2).is_ok());
+        assert!(ctx
+            .validate_message_value_hash_chain(MESSAGE_VALUE_2, Some(MESSAGE_VALUE_1))
+            .is_ok());
+        assert!(ctx
+            .validate_message_hash_chain(MESSAGE_2, Some(MESSAGE_1))
+            .is_ok());
+    }
+
+    #[test]
+    fn it_applies_its_options() {
+        let ctx = ValidatorContext::with_options(ValidationOptions {
+            max_value_length: 256,
+            ..Default::default()
+        });
+        match ctx.validate_message_value(MESSAGE_VALUE_2) {
+            Err(Error::InvalidMessageValueLength { limit: 256, .. }) => {}
+            _ => panic!(),
+        }
+    }
+}
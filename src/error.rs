@@ -10,6 +10,8 @@ use snafu::Snafu;
 use ssb_legacy_msg_data::json::{DecodeJsonError, EncodeJsonError};
 use ssb_multiformats::multihash::Multihash;
 
+use crate::utils::MAX_MESSAGE_VALUE_UTF16_UNITS;
+
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
 #[derive(Debug, Snafu)]
@@ -25,8 +27,21 @@ pub enum Error {
         source: DecodeJsonError,
         message: Vec<u8>,
     },
-    #[snafu(display("Message must have keys in correct order",))]
-    InvalidMessageValueOrder { message: Vec<u8> },
+    #[snafu(display(
+        "The message value has an unexpected field `{}` (only previous, author, sequence, timestamp, hash, content and signature are allowed)",
+        field
+    ))]
+    UnexpectedMessageValueField { message: Vec<u8>, field: String },
+    #[snafu(display(
+        "Message must have keys in correct order. Expected: previous, author, sequence, timestamp, hash, content, signature\nFound: {}",
+        found_order.join(", ")
+    ))]
+    InvalidMessageValueOrder {
+        message: Vec<u8>,
+        found_order: Vec<String>,
+    },
+    #[snafu(display("The message key is not a valid message hash: {}", key))]
+    InvalidKeyFormat { message: Vec<u8>, key: String },
     #[snafu(display(
         "Message was invalid. The authors did not match. \nAuthor of previous: {}\n Author: {} ",
         previous_author,
@@ -36,32 +51,120 @@ pub enum Error {
         previous_author: String,
         author: String,
     },
+    #[snafu(display("Message was invalid. The author is not allowed: {}", author))]
+    AuthorNotAllowed { message: Vec<u8>, author: String },
+    #[snafu(display(
+        "Message was invalid. It starts with a UTF-8 byte order mark, which is not valid JSON"
+    ))]
+    LeadingBom { message: Vec<u8> },
+    #[snafu(display("The author is not a valid feed id: {}", author))]
+    InvalidAuthorFormat { author: String },
+    #[snafu(display("The signature of the message is not a valid ed25519 signature"))]
+    InvalidSignatureFormat { message: Vec<u8> },
     #[snafu(display("The first message of a feed must have seq of 1",))]
     FirstMessageDidNotHaveSequenceOfOne { message: Vec<u8> },
     #[snafu(display("The first message of a feed must have previous of null",))]
     FirstMessageDidNotHavePreviousOfNull { message: Vec<u8> },
+    #[snafu(display(
+        "The message must have a previous of null if, and only if, it has a seq of 1",
+    ))]
+    InconsistentFirstMessageMarkers { message: Vec<u8> },
     #[snafu(display("The message hash must be 'sha256'",))]
     InvalidHashFunction { message: Vec<u8> },
+    #[snafu(display(
+        "The message timestamp must be a non-negative number (and a whole number, if required)",
+    ))]
+    InvalidTimestamp { message: Vec<u8> },
+    #[snafu(display("The message receive timestamp must be a non-negative number",))]
+    InvalidReceiveTimestamp { message: Vec<u8> },
+    #[snafu(display(
+        "The message timestamp must not be less than the timestamp of the previous message. \nTimestamp of previous: {}\nTimestamp: {}",
+        previous,
+        current
+    ))]
+    NonMonotonicTimestamp { previous: f64, current: f64 },
+    #[snafu(display(
+        "The message content must not be nested more than {} levels deep",
+        max_depth
+    ))]
+    ContentTooDeep { message: Vec<u8>, max_depth: usize },
+    #[snafu(display("The message content mentions an invalid link: {}", link))]
+    InvalidLink { message: Vec<u8>, link: String },
+    #[snafu(display("The message content was invalid: {}", reason))]
+    InvalidContent {
+        message: Vec<u8>,
+        reason: &'static str,
+    },
+    #[snafu(display(
+        "The message content type must be between 3 and 52 UTF-8 bytes long: {}",
+        content_type
+    ))]
+    InvalidContentType {
+        message: Vec<u8>,
+        content_type: String,
+    },
     #[snafu(display("The message content string must be canonical base64",))]
     InvalidBase64 { message: Vec<u8> },
-    #[snafu(display("The message value must not be longer than 8192 UTF-16 code units",))]
-    InvalidMessageValueLength { message: Vec<u8> },
+    #[snafu(display(
+        "The message content ciphertext is too short to be a private-box: {} bytes, minimum {}",
+        length,
+        minimum
+    ))]
+    CiphertextTooShort {
+        message: Vec<u8>,
+        length: usize,
+        minimum: usize,
+    },
+    #[snafu(display(
+        "The message value must not be longer than {} UTF-16 code units. Actual length: {}",
+        limit,
+        actual
+    ))]
+    InvalidMessageValueLength {
+        message: Vec<u8>,
+        actual: usize,
+        limit: usize,
+    },
     #[snafu(display("The sequence must increase by one",))]
     InvalidSequenceNumber {
         message: Vec<u8>,
         actual: u64,
         expected: u64,
     },
+    #[snafu(display("The sequence must be at least 1"))]
+    SequenceWasZero { message: Vec<u8> },
+    #[snafu(display("The sequence of the previous message is too large to be followed"))]
+    SequenceOverflow { message: Vec<u8> },
     #[snafu(display("Unable to get the value from the message, the message was invalid"))]
     InvalidMessageNoValue,
     #[snafu(display("Could not serialize message.value to bytes. Failed with: {}", source))]
     InvalidMessageCouldNotSerializeValue { source: EncodeJsonError },
-    #[snafu(display("The actual hash of the value did not match the hash claimed by `key`"))]
+    #[snafu(display(
+        "The actual hash of the value ({}) did not match the hash claimed by `key` ({})",
+        actual_hash.to_legacy_string(),
+        expected_hash.to_legacy_string()
+    ))]
     ActualHashDidNotMatchKey {
         message: Vec<u8>,
         actual_hash: Multihash,
         expected_hash: Multihash,
     },
+    #[snafu(display(
+        "The key of the previous message ({}) is not the hash of its value ({})",
+        expected_hash.to_legacy_string(),
+        actual_hash.to_legacy_string()
+    ))]
+    PreviousKeyMismatch {
+        message: Vec<u8>,
+        actual_hash: Multihash,
+        expected_hash: Multihash,
+    },
+    #[snafu(display("The signature of the message could not be verified against its author"))]
+    InvalidSignature { message: Vec<u8> },
+    #[snafu(display("The previous reference must be a message hash (not a blob hash)"))]
+    InvalidPreviousReference { message: Vec<u8> },
+    #[snafu(display("The previous reference of the message refers to the message itself"))]
+    SelfReferentialPrevious { message: Vec<u8> },
     #[snafu(display("Previous was set to null but it should have had a value"))]
     PreviousWasNull,
     #[snafu(display(
@@ -69,4 +172,278 @@ pub enum Error {
         previous_seq
     ))]
     ForkedFeed { previous_seq: u64 },
+    #[snafu(display(
+        "The messages do not form a single chain from the first message: {} could not be reached",
+        unchained
+    ))]
+    BrokenChain { unchained: usize },
+    #[snafu(display("Bendy butt message was invalid: {}", reason))]
+    InvalidBendyButtMessage {
+        message: Vec<u8>,
+        reason: &'static str,
+    },
+    #[snafu(display("The log entry at offset {} was invalid: {}", offset, source))]
+    InvalidLogEntry {
+        offset: u64,
+        #[snafu(source(from(Error, Box::new)))]
+        source: Box<Error>,
+    },
+    #[snafu(display("Validation was cancelled"))]
+    Cancelled,
+    #[snafu(display("Gabby grove message was invalid: {}", reason))]
+    InvalidGabbyGroveMessage {
+        message: Vec<u8>,
+        reason: &'static str,
+    },
+}
+
+/// The kind of an [`Error`], without its context. Useful for tallying errors (for example, in
+/// [`BatchStats`](crate::message::BatchStats)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    InvalidPreviousMessage,
+    InvalidMessage,
+    UnexpectedMessageValueField,
+    InvalidMessageValueOrder,
+    InvalidKeyFormat,
+    AuthorsDidNotMatch,
+    AuthorNotAllowed,
+    LeadingBom,
+    InvalidAuthorFormat,
+    InvalidSignatureFormat,
+    FirstMessageDidNotHaveSequenceOfOne,
+    FirstMessageDidNotHavePreviousOfNull,
+    InconsistentFirstMessageMarkers,
+    InvalidHashFunction,
+    InvalidTimestamp,
+    InvalidReceiveTimestamp,
+    NonMonotonicTimestamp,
+    ContentTooDeep,
+    InvalidLink,
+    InvalidContent,
+    InvalidContentType,
+    InvalidBase64,
+    CiphertextTooShort,
+    InvalidMessageValueLength,
+    InvalidSequenceNumber,
+    SequenceWasZero,
+    SequenceOverflow,
+    InvalidMessageNoValue,
+    InvalidMessageCouldNotSerializeValue,
+    ActualHashDidNotMatchKey,
+    PreviousKeyMismatch,
+    InvalidSignature,
+    InvalidPreviousReference,
+    SelfReferentialPrevious,
+    PreviousWasNull,
+    ForkedFeed,
+    BrokenChain,
+    InvalidBendyButtMessage,
+    InvalidLogEntry,
+    Cancelled,
+    InvalidGabbyGroveMessage,
+}
+
+/// A coarse grouping of errors, for services which report validation failures to their clients
+/// (for example, as an HTTP status code with [`status_hint`](ErrorCategory::status_hint)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// The message is not a valid message by the feed specification, whatever feed it is part of.
+    Malformed,
+    /// The message is valid by itself but does not follow on from the previous message of its
+    /// feed (for example, a fork or a gap in the sequence).
+    Conflict,
+    /// The message was rejected by an optional check (see
+    /// [`ValidationOptions`](crate::options::ValidationOptions)) or an allowlist.
+    PolicyViolation,
+    /// Validation could not be completed, through no fault of the message.
+    Internal,
+}
+
+impl ErrorCategory {
+    /// The HTTP status code which best describes errors of this category: `400` for
+    /// [`Malformed`](ErrorCategory::Malformed), `409` for [`Conflict`](ErrorCategory::Conflict),
+    /// `422` for [`PolicyViolation`](ErrorCategory::PolicyViolation) and `500` for
+    /// [`Internal`](ErrorCategory::Internal).
+    pub fn status_hint(self) -> u16 {
+        match self {
+            ErrorCategory::Malformed => 400,
+            ErrorCategory::Conflict => 409,
+            ErrorCategory::PolicyViolation => 422,
+            ErrorCategory::Internal => 500,
+        }
+    }
+}
+
+impl ErrorKind {
+    /// The category of errors of this kind.
+    ///
+    /// An `InvalidLogEntry` is categorized as `Malformed`, as is an `InvalidMessageValueLength`
+    /// (exceeding the limit of the feed specification); use [`Error::category`] to categorize
+    /// these by their context instead.
+    pub fn category(self) -> ErrorCategory {
+        match self {
+            ErrorKind::AuthorsDidNotMatch
+            | ErrorKind::FirstMessageDidNotHaveSequenceOfOne
+            | ErrorKind::FirstMessageDidNotHavePreviousOfNull
+            | ErrorKind::InvalidSequenceNumber
+            | ErrorKind::SequenceOverflow
+            | ErrorKind::PreviousWasNull
+            | ErrorKind::ForkedFeed
+            | ErrorKind::BrokenChain => ErrorCategory::Conflict,
+            ErrorKind::AuthorNotAllowed
+            | ErrorKind::NonMonotonicTimestamp
+            | ErrorKind::ContentTooDeep
+            | ErrorKind::InvalidLink
+            | ErrorKind::InvalidContent
+            | ErrorKind::CiphertextTooShort
+            | ErrorKind::InvalidReceiveTimestamp => ErrorCategory::PolicyViolation,
+            ErrorKind::InvalidMessageCouldNotSerializeValue | ErrorKind::Cancelled => {
+                ErrorCategory::Internal
+            }
+            ErrorKind::InvalidPreviousMessage
+            | ErrorKind::InvalidMessage
+            | ErrorKind::UnexpectedMessageValueField
+            | ErrorKind::InvalidMessageValueOrder
+            | ErrorKind::InvalidKeyFormat
+            | ErrorKind::LeadingBom
+            | ErrorKind::InvalidAuthorFormat
+            | ErrorKind::InvalidSignatureFormat
+            | ErrorKind::InconsistentFirstMessageMarkers
+            | ErrorKind::InvalidHashFunction
+            | ErrorKind::InvalidTimestamp
+            | ErrorKind::InvalidContentType
+            | ErrorKind::InvalidBase64
+            | ErrorKind::InvalidMessageValueLength
+            | ErrorKind::SequenceWasZero
+            | ErrorKind::InvalidMessageNoValue
+            | ErrorKind::ActualHashDidNotMatchKey
+            | ErrorKind::PreviousKeyMismatch
+            | ErrorKind::InvalidSignature
+            | ErrorKind::InvalidPreviousReference
+            | ErrorKind::SelfReferentialPrevious
+            | ErrorKind::InvalidBendyButtMessage
+            | ErrorKind::InvalidLogEntry
+            | ErrorKind::InvalidGabbyGroveMessage => ErrorCategory::Malformed,
+        }
+    }
+}
+
+impl Error {
+    /// The category of this error. An `InvalidLogEntry` has the category of its source, and an
+    /// `InvalidMessageValueLength` is a `PolicyViolation` if the message value is within the
+    /// limit of the feed specification (so only a lower, configured limit was exceeded).
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            Error::InvalidLogEntry { source, .. } => source.category(),
+            Error::InvalidMessageValueLength { actual, .. }
+                if *actual <= MAX_MESSAGE_VALUE_UTF16_UNITS =>
+            {
+                ErrorCategory::PolicyViolation
+            }
+            _ => self.kind().category(),
+        }
+    }
+
+    /// The HTTP status code which best describes this error (see
+    /// [`ErrorCategory::status_hint`]).
+    pub fn as_status_hint(&self) -> u16 {
+        self.category().status_hint()
+    }
+
+    /// The kind of this error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::InvalidPreviousMessage { .. } => ErrorKind::InvalidPreviousMessage,
+            Error::InvalidMessage { .. } => ErrorKind::InvalidMessage,
+            Error::UnexpectedMessageValueField { .. } => ErrorKind::UnexpectedMessageValueField,
+            Error::InvalidMessageValueOrder { .. } => ErrorKind::InvalidMessageValueOrder,
+            Error::InvalidKeyFormat { .. } => ErrorKind::InvalidKeyFormat,
+            Error::AuthorsDidNotMatch { .. } => ErrorKind::AuthorsDidNotMatch,
+            Error::AuthorNotAllowed { .. } => ErrorKind::AuthorNotAllowed,
+            Error::LeadingBom { .. } => ErrorKind::LeadingBom,
+            Error::InvalidAuthorFormat { .. } => ErrorKind::InvalidAuthorFormat,
+            Error::InvalidSignatureFormat { .. } => ErrorKind::InvalidSignatureFormat,
+            Error::FirstMessageDidNotHaveSequenceOfOne { .. } => {
+                ErrorKind::FirstMessageDidNotHaveSequenceOfOne
+            }
+            Error::FirstMessageDidNotHavePreviousOfNull { .. } => {
+                ErrorKind::FirstMessageDidNotHavePreviousOfNull
+            }
+            Error::InconsistentFirstMessageMarkers { .. } => {
+                ErrorKind::InconsistentFirstMessageMarkers
+            }
+            Error::InvalidHashFunction { .. } => ErrorKind::InvalidHashFunction,
+            Error::InvalidTimestamp { .. } => ErrorKind::InvalidTimestamp,
+            Error::InvalidReceiveTimestamp { .. } => ErrorKind::InvalidReceiveTimestamp,
+            Error::NonMonotonicTimestamp { .. } => ErrorKind::NonMonotonicTimestamp,
+            Error::ContentTooDeep { .. } => ErrorKind::ContentTooDeep,
+            Error::InvalidLink { .. } => ErrorKind::InvalidLink,
+            Error::InvalidContent { .. } => ErrorKind::InvalidContent,
+            Error::InvalidContentType { .. } => ErrorKind::InvalidContentType,
+            Error::InvalidBase64 { .. } => ErrorKind::InvalidBase64,
+            Error::CiphertextTooShort { .. } => ErrorKind::CiphertextTooShort,
+            Error::InvalidMessageValueLength { .. } => ErrorKind::InvalidMessageValueLength,
+            Error::InvalidSequenceNumber { .. } => ErrorKind::InvalidSequenceNumber,
+            Error::SequenceWasZero { .. } => ErrorKind::SequenceWasZero,
+            Error::SequenceOverflow { .. } => ErrorKind::SequenceOverflow,
+            Error::InvalidMessageNoValue => ErrorKind::InvalidMessageNoValue,
+            Error::InvalidMessageCouldNotSerializeValue { .. } => {
+                ErrorKind::InvalidMessageCouldNotSerializeValue
+            }
+            Error::ActualHashDidNotMatchKey { .. } => ErrorKind::ActualHashDidNotMatchKey,
+            Error::PreviousKeyMismatch { .. } => ErrorKind::PreviousKeyMismatch,
+            Error::InvalidSignature { .. } => ErrorKind::InvalidSignature,
+            Error::InvalidPreviousReference { .. } => ErrorKind::InvalidPreviousReference,
+            Error::SelfReferentialPrevious { .. } => ErrorKind::SelfReferentialPrevious,
+            Error::PreviousWasNull => ErrorKind::PreviousWasNull,
+            Error::ForkedFeed { .. } => ErrorKind::ForkedFeed,
+            Error::BrokenChain { .. } => ErrorKind::BrokenChain,
+            Error::InvalidBendyButtMessage { .. } => ErrorKind::InvalidBendyButtMessage,
+            Error::InvalidLogEntry { .. } => ErrorKind::InvalidLogEntry,
+            Error::Cancelled => ErrorKind::Cancelled,
+            Error::InvalidGabbyGroveMessage { .. } => ErrorKind::InvalidGabbyGroveMessage,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::{Error, ErrorCategory};
+
+    #[test]
+    fn it_categorizes_errors() {
+        assert_eq!(Error::PreviousWasNull.category(), ErrorCategory::Conflict);
+        assert_eq!(Error::ForkedFeed { previous_seq: 1 }.as_status_hint(), 409);
+        assert_eq!(
+            Error::InvalidSignature { message: vec![] }.as_status_hint(),
+            400
+        );
+        assert_eq!(
+            Error::AuthorNotAllowed {
+                message: vec![],
+                author: String::new()
+            }
+            .as_status_hint(),
+            422
+        );
+        assert_eq!(Error::Cancelled.as_status_hint(), 500);
+
+        let too_long = |actual, limit| Error::InvalidMessageValueLength {
+            message: vec![],
+            actual,
+            limit,
+        };
+        assert_eq!(too_long(8193, 8192).category(), ErrorCategory::Malformed);
+        assert_eq!(
+            too_long(300, 256).category(),
+            ErrorCategory::PolicyViolation
+        );
+
+        let err = Error::InvalidLogEntry {
+            offset: 0,
+            source: Box::new(Error::ForkedFeed { previous_seq: 1 }),
+        };
+        assert_eq!(err.category(), ErrorCategory::Conflict);
+    }
 }
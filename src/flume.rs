@@ -0,0 +1,77 @@
+//! Validate the messages stored in a [flumedb](https://docs.rs/flumedb) `OffsetLog`.
+//!
+//! Requires the `flume` feature.
+use flumedb::OffsetLog;
+use snafu::ResultExt;
+
+use crate::error::{InvalidLogEntry, Result};
+use crate::message::{validate_message_hash_chain, validate_message_hash_chain_in_batch};
+use crate::options::ValidationOptions;
+
+/// Validate the entries of an offset log as a single feed, returning the number of entries
+/// validated.
+///
+/// Each entry is expected to be a JSON encoded message of shape: `{key: "", value: {...}}`. The
+/// first entry must be the first message of the feed, and each following entry is validated in
+/// relation to the entry before it (see [`validate_message_hash_chain`]). The first invalid entry
+/// is reported as `Error::InvalidLogEntry`, holding the offset of the entry in the log and the
+/// validation error.
+pub fn validate_offset_log(log: &OffsetLog<u32>) -> Result<usize> {
+    let mut previous: Option<Vec<u8>> = None;
+    let mut count = 0;
+
+    for entry in log.iter() {
+        // The key of the previous entry was checked when it was validated.
+        let result = match previous.as_ref() {
+            Some(previous) => validate_message_hash_chain_in_batch(
+                &entry.data,
+                previous,
+                &ValidationOptions::default(),
+            )
+            .map(|_| ()),
+            None => validate_message_hash_chain::<_, &[u8]>(&entry.data, None),
+        };
+        result.context(InvalidLogEntry {
+            offset: entry.offset,
+        })?;
+        previous = Some(entry.data);
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use flumedb::OffsetLog;
+
+    use crate::error::Error;
+    use crate::flume::validate_offset_log;
+    use crate::test_data::{MESSAGE_1, MESSAGE_3};
+
+    #[test]
+    fn it_validates_an_offset_log() {
+        let log = OffsetLog::<u32>::open_read_only("./test_vecs/piet.offset").unwrap();
+        let count = validate_offset_log(&log).unwrap();
+        assert_eq!(count, log.iter().count());
+    }
+
+    #[test]
+    fn it_reports_the_offset_of_an_invalid_entry() {
+        let path = std::env::temp_dir().join(format!("ssb-validate-{}.offset", std::process::id()));
+        let mut log = OffsetLog::<u32>::new(&path).unwrap();
+        let offsets = log
+            .append_batch(&[MESSAGE_1.as_bytes(), MESSAGE_3.as_bytes()])
+            .unwrap();
+
+        let result = validate_offset_log(&log);
+        std::fs::remove_file(&path).unwrap();
+        match result {
+            Err(Error::InvalidLogEntry { offset, source }) => {
+                assert_eq!(offset, offsets[1]);
+                assert!(matches!(*source, Error::InvalidSequenceNumber { .. }));
+            }
+            _ => panic!(),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+//! Entry point for fuzzing the validation functions with arbitrary input.
+//!
+//! The `fuzz` directory of the repository contains a [`cargo fuzz`](https://github.com/rust-fuzz/cargo-fuzz)
+//! target which calls [`fuzz_validate`], along with a seed corpus generated from the messages in
+//! [`test_data`](crate::test_data). Validation must return an error for malformed input and must
+//! never panic.
+use crate::message::{
+    validate_message_hash_chain, validate_multi_author_message_hash_chain,
+    validate_ooo_message_hash_chain,
+};
+use crate::message_value::{
+    validate_message_value, validate_message_value_hash_chain,
+    validate_ooo_message_value_hash_chain,
+};
+
+/// Feed arbitrary bytes through the validation functions, discarding the results.
+///
+/// If the input contains a NUL byte, the bytes before it are used as the message and the bytes
+/// after it as the previous message, so that the hash chain checks are exercised too.
+pub fn fuzz_validate(data: &[u8]) {
+    let (message, previous) = match data.iter().position(|byte| *byte == 0) {
+        Some(idx) => (&data[..idx], Some(&data[idx + 1..])),
+        None => (data, None),
+    };
+
+    let _ = validate_message_hash_chain(message, previous);
+    let _ = validate_ooo_message_hash_chain(message, previous);
+    let _ = validate_multi_author_message_hash_chain(message);
+    let _ = validate_message_value_hash_chain(message, previous);
+    let _ = validate_ooo_message_value_hash_chain(message, previous);
+    let _ = validate_message_value(message);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fuzz::fuzz_validate;
+    use crate::test_data::*;
+
+    #[test]
+    fn it_does_not_panic_on_mutated_test_data() {
+        let seeds = [MESSAGE_2, MESSAGE_VALUE_2, MESSAGE_WITH_UNICODE];
+        for seed in seeds.iter() {
+            let seed = seed.as_bytes();
+            for len in 0..seed.len() {
+                fuzz_validate(&seed[..len]);
+            }
+            for idx in 0..seed.len() {
+                for byte in [0u8, b'"', 0xff].iter() {
+                    let mut mutated = seed.to_vec();
+                    mutated[idx] = *byte;
+                    fuzz_validate(&mutated);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//! Functions for validating [Gabby Grove](https://github.com/ssbc/ssb-spec-drafts/tree/master/drafts/draft-ssb-core-gabbygrove/00)
+//! (`ggfeed-v1`) messages.
+//!
+//! A Gabby Grove message is transferred as a [CBOR](https://tools.ietf.org/html/rfc7049) array
+//! of three elements: `[event, signature, content]`. The event is itself a CBOR-encoded byte
+//! string holding an array of five elements: `[previous, author, sequence, timestamp,
+//! contentInfo]`, where `contentInfo` is `[hash, size, type]`. Feed ids, message ids and content
+//! hashes are binary references: byte strings tagged with CBOR tag 1050, holding a type byte
+//! followed by the 32 bytes of the key or hash.
+//!
+//! The key of a message is the `sha256` hash of the encoded event. It is this hash which must
+//! be referenced by the `previous` field of the next message in the feed. The content itself is
+//! not part of the event, only its hash and size are, so the content may be omitted (`null`)
+//! when it is transferred. When the content is present it must match that hash and size.
+//!
+//! As with the other feed formats, this module does not perform signature verification.
+use sha2::{Digest, Sha256};
+
+use crate::error::{InvalidGabbyGroveMessage, Result};
+use crate::utils::{self, BinaryMessage};
+
+/// Maximum length of an encoded message, in bytes.
+const MAX_MESSAGE_LENGTH: usize = 16384;
+/// Maximum nesting depth of arrays and tags accepted by the CBOR decoder.
+const MAX_NESTING_DEPTH: usize = 8;
+
+/// CBOR tag of a binary reference.
+const BINARY_REF_TAG: u64 = 1050;
+/// Binary reference type of an ed25519 feed id.
+const REF_TYPE_FEED: u8 = 0x01;
+/// Binary reference type of a Gabby Grove message id.
+const REF_TYPE_MESSAGE: u8 = 0x02;
+/// Binary reference type of a content hash.
+const REF_TYPE_CONTENT: u8 = 0x03;
+/// Highest valid content type (0 is arbitrary bytes, 1 is JSON, 2 is CBOR).
+const MAX_CONTENT_TYPE: u64 = 2;
+
+/// A decoded CBOR value, borrowing byte strings from the input.
+enum Cbor<'a> {
+    Unsigned(u64),
+    Negative(u64),
+    Bytes(&'a [u8]),
+    Array(Vec<Cbor<'a>>),
+    Tag(u64, Box<Cbor<'a>>),
+    Null,
+}
+
+/// Validate a Gabby Grove message, optionally in relation to the previous message.
+///
+/// This checks that:
+/// - the message is canonical CBOR and does not exceed 16384 bytes
+/// - the message is an `[event, signature, content]` triple and the event has five elements
+/// - the author is an ed25519 feed id and the signature is 64 bytes long
+/// - if the content is present, its `sha256` hash and size match those in the event
+/// - the sequence starts at one and the previous is null if it's the first message
+/// - the sequence increments correctly
+/// - the author has not changed
+/// - the previous matches the key (`sha256` hash of the event) of the previous message,
+///   otherwise the feed is forked
+///
+/// `previous_msg_bytes` will be `None` only when `message_bytes` is the first message by that author.
+///
+/// This does not check the signature.
+pub fn validate_gabby_grove_message<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+    message_bytes: T,
+    previous_msg_bytes: Option<U>,
+) -> Result<()> {
+    let message_bytes = message_bytes.as_ref();
+    let message = decode_message(message_bytes)?;
+
+    let previous = match previous_msg_bytes {
+        Some(previous_bytes) => Some(decode_message(previous_bytes.as_ref())?),
+        None => None,
+    };
+
+    utils::check_binary_continuity(&message, previous.as_ref(), message_bytes, feed_id_string)
+}
+
+/// Decode a message and check its structure, mapping any problem to
+/// `Error::InvalidGabbyGroveMessage`.
+fn decode_message(message_bytes: &[u8]) -> Result<BinaryMessage> {
+    match parse_message(message_bytes) {
+        Ok(event) => Ok(event),
+        Err(reason) => InvalidGabbyGroveMessage {
+            message: message_bytes.to_owned(),
+            reason,
+        }
+        .fail(),
+    }
+}
+
+/// Parse the structure of a message, returning a description of the first problem found.
+fn parse_message(message_bytes: &[u8]) -> std::result::Result<BinaryMessage, &'static str> {
+    if message_bytes.len() > MAX_MESSAGE_LENGTH {
+        return Err("message must not be longer than 16384 bytes");
+    }
+
+    let message = decode(message_bytes).ok_or("message is not canonical CBOR")?;
+    let (event_bytes, signature, content) = match message {
+        Cbor::Array(ref items) if items.len() == 3 => match items[0] {
+            Cbor::Bytes(event_bytes) => (event_bytes, &items[1], &items[2]),
+            _ => return Err("event must be a byte string"),
+        },
+        _ => return Err("message must be an array of event, signature and content"),
+    };
+
+    match *signature {
+        Cbor::Bytes(signature) if signature.len() == 64 => (),
+        _ => return Err("signature must be 64 bytes"),
+    }
+
+    let event = decode(event_bytes).ok_or("event is not canonical CBOR")?;
+    let fields = match event {
+        Cbor::Array(ref items) if items.len() == 5 => items,
+        _ => return Err("event must be an array of five elements"),
+    };
+
+    let previous = match fields[0] {
+        Cbor::Null => None,
+        ref previous => Some(binary_ref(previous, REF_TYPE_MESSAGE).ok_or("invalid previous")?),
+    };
+
+    let author = binary_ref(&fields[1], REF_TYPE_FEED).ok_or("invalid author")?;
+
+    let sequence = match fields[2] {
+        Cbor::Unsigned(sequence) if sequence >= 1 => sequence,
+        _ => return Err("sequence must be a positive integer"),
+    };
+
+    // The timestamp is a signed 64 bit integer; a negative value `n` encodes `-1 - n`.
+    match fields[3] {
+        Cbor::Unsigned(timestamp) | Cbor::Negative(timestamp) if timestamp <= i64::MAX as u64 => (),
+        _ => return Err("timestamp must be a 64 bit integer"),
+    }
+
+    let (content_hash, content_size) = match fields[4] {
+        Cbor::Array(ref info) if info.len() == 3 => {
+            let hash = binary_ref(&info[0], REF_TYPE_CONTENT).ok_or("invalid content hash")?;
+            let size = match info[1] {
+                Cbor::Unsigned(size) => size,
+                _ => return Err("content size must be an unsigned integer"),
+            };
+            match info[2] {
+                Cbor::Unsigned(content_type) if content_type <= MAX_CONTENT_TYPE => (),
+                _ => return Err("invalid content type"),
+            }
+            (hash, size)
+        }
+        _ => return Err("content info must be an array of hash, size and type"),
+    };
+
+    match *content {
+        Cbor::Null => (),
+        Cbor::Bytes(content) => {
+            if content.len() as u64 != content_size {
+                return Err("content size does not match the event");
+            }
+            if Sha256::digest(content).as_slice() != content_hash {
+                return Err("content hash does not match the event");
+            }
+        }
+        _ => return Err("content must be a byte string or null"),
+    }
+
+    Ok(BinaryMessage {
+        author: to_key(author),
+        sequence,
+        previous: previous.map(to_key),
+        key: Sha256::digest(event_bytes).into(),
+    })
+}
+
+/// Return the 32 bytes of a binary reference, if it has the given reference type.
+fn binary_ref<'a>(value: &Cbor<'a>, ref_type: u8) -> Option<&'a [u8]> {
+    match *value {
+        Cbor::Tag(BINARY_REF_TAG, ref inner) => match **inner {
+            Cbor::Bytes(bytes) if bytes.len() == 33 && bytes[0] == ref_type => Some(&bytes[1..]),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Copy the 32 bytes of a binary reference into an array.
+fn to_key(bytes: &[u8]) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    key.copy_from_slice(bytes);
+    key
+}
+
+/// Format a Gabby Grove feed id for use in error messages.
+fn feed_id_string(author: &[u8; 32]) -> String {
+    format!("@{}.ggfeed-v1", base64::encode(author))
+}
+
+/// Decode a complete CBOR value, rejecting trailing data and non-canonical encodings.
+fn decode(bytes: &[u8]) -> Option<Cbor<'_>> {
+    match decode_partial(bytes, 0)? {
+        (value, []) => Some(value),
+        _ => None,
+    }
+}
+
+/// Decode a single CBOR value from the start of `bytes`, returning it along with the remaining
+/// input. Only the subset of CBOR used by Gabby Grove is supported: integers, byte strings,
+/// definite-length arrays, tags and null.
+fn decode_partial(bytes: &[u8], depth: usize) -> Option<(Cbor<'_>, &[u8])> {
+    if depth > MAX_NESTING_DEPTH {
+        return None;
+    }
+
+    let initial = *bytes.first()?;
+    if initial == 0xf6 {
+        return Some((Cbor::Null, &bytes[1..]));
+    }
+
+    let (argument, rest) = decode_argument(bytes)?;
+    match initial >> 5 {
+        0 => Some((Cbor::Unsigned(argument), rest)),
+        1 => Some((Cbor::Negative(argument), rest)),
+        2 => {
+            if argument > rest.len() as u64 {
+                return None;
+            }
+            let (string, rest) = rest.split_at(argument as usize);
+            Some((Cbor::Bytes(string), rest))
+        }
+        4 => {
+            // Every element takes at least one byte, which bounds the allocation.
+            if argument > rest.len() as u64 {
+                return None;
+            }
+            let mut items = Vec::with_capacity(argument as usize);
+            let mut rest = rest;
+            for _ in 0..argument {
+                let (item, tail) = decode_partial(rest, depth + 1)?;
+                items.push(item);
+                rest = tail;
+            }
+            Some((Cbor::Array(items), rest))
+        }
+        6 => {
+            let (item, rest) = decode_partial(rest, depth + 1)?;
+            Some((Cbor::Tag(argument, Box::new(item)), rest))
+        }
+        _ => None,
+    }
+}
+
+/// Decode the argument of a CBOR data item head, requiring the shortest possible encoding.
+fn decode_argument(bytes: &[u8]) -> Option<(u64, &[u8])> {
+    let additional = bytes[0] & 0x1f;
+    let rest = &bytes[1..];
+    let (len, min) = match additional {
+        0..=23 => return Some((u64::from(additional), rest)),
+        24 => (1, 24),
+        25 => (2, 0x100),
+        26 => (4, 0x1_0000),
+        27 => (8, 0x1_0000_0000),
+        _ => return None,
+    };
+    if rest.len() < len {
+        return None;
+    }
+    let argument = rest[..len]
+        .iter()
+        .fold(0u64, |acc, &byte| (acc << 8) | u64::from(byte));
+    if argument < min {
+        return None;
+    }
+    Some((argument, &rest[len..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::gabby_grove::validate_gabby_grove_message;
+    use crate::test_data::{GABBY_GROVE_MESSAGE_1, GABBY_GROVE_MESSAGE_2};
+
+    #[test]
+    fn it_validates_a_gabby_grove_hash_chain() {
+        assert!(validate_gabby_grove_message::<_, &[u8]>(GABBY_GROVE_MESSAGE_1, None).is_ok());
+        assert!(
+            validate_gabby_grove_message(GABBY_GROVE_MESSAGE_2, Some(GABBY_GROVE_MESSAGE_1))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn it_detects_a_gabby_grove_fork() {
+        let result =
+            validate_gabby_grove_message(GABBY_GROVE_MESSAGE_2, Some(GABBY_GROVE_MESSAGE_2));
+        match result {
+            Err(Error::InvalidSequenceNumber {
+                message: _,
+                actual: 2,
+                expected: 3,
+            }) => {}
+            _ => panic!(),
+        }
+
+        let result = validate_gabby_grove_message::<_, &[u8]>(GABBY_GROVE_MESSAGE_2, None);
+        match result {
+            Err(Error::FirstMessageDidNotHaveSequenceOfOne { message: _ }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_detects_gabby_grove_content_that_does_not_match_its_hash() {
+        let mut message = GABBY_GROVE_MESSAGE_1.to_vec();
+        // The content is the final element, so changing the last byte changes the content.
+        let last = message.len() - 1;
+        message[last] = b']';
+        match validate_gabby_grove_message::<_, &[u8]>(&message, None) {
+            Err(Error::InvalidGabbyGroveMessage {
+                message: _,
+                reason: "content hash does not match the event",
+            }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_detects_invalid_cbor() {
+        let truncated = &GABBY_GROVE_MESSAGE_1[..GABBY_GROVE_MESSAGE_1.len() - 1];
+        match validate_gabby_grove_message::<_, &[u8]>(truncated, None) {
+            Err(Error::InvalidGabbyGroveMessage {
+                message: _,
+                reason: _,
+            }) => {}
+            _ => panic!(),
+        }
+    }
+}
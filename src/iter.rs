@@ -0,0 +1,155 @@
+//! Lazy validation of a feed of message values supplied by an iterator.
+//!
+//! This is an alternative to the batch functions in [`message_value`](crate::message_value) for
+//! callers who read messages one at a time (for example, from a database cursor) and would
+//! rather not collect them into a slice first.
+use ssb_multiformats::multihash::Multihash;
+
+use crate::error::Result;
+use crate::message_value::{validate_message_value_against_previous, SsbMessageValue};
+use crate::options::ValidationOptions;
+use crate::utils;
+
+/// Iterator adapter which validates each message value of a feed in relation to the one before
+/// it, yielding the parsed message values.
+///
+/// The first item is validated as the first message of the feed (`sequence` of 1 and `previous`
+/// of `null`). Once an item fails validation the error is yielded and the iterator ends, since
+/// the messages which follow can no longer be validated against a known-good previous message.
+///
+/// # Example
+///```
+///use ssb_validate::iter::ValidatingIterExt;
+///let valid_message_1 = r##"{
+///  "previous": null,
+///  "author": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+///  "sequence": 1,
+///  "timestamp": 1470186877575,
+///  "hash": "sha256",
+///  "content": {
+///    "type": "about",
+///    "about": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+///    "name": "Piet"
+///  },
+///  "signature": "QJKWui3oyK6r5dH13xHkEVFhfMZDTXfK2tW21nyfheFClSf69yYK77Itj1BGcOimZ16pj9u3tMArLUCGSscqCQ==.sig.ed25519"
+///}"##;
+/// let messages = vec![valid_message_1.as_bytes().to_vec()];
+/// for result in messages.into_iter().validating() {
+///     let message_value = result.unwrap();
+///     assert_eq!(message_value.sequence, 1);
+/// }
+///```
+pub struct ValidatingIter<I> {
+    messages: I,
+    previous: Option<(SsbMessageValue, Multihash)>,
+    failed: bool,
+}
+
+impl<I> ValidatingIter<I> {
+    /// Wrap an iterator of JSON encoded message values, starting from the first message of a feed.
+    pub fn new(messages: I) -> ValidatingIter<I> {
+        ValidatingIter {
+            messages,
+            previous: None,
+            failed: false,
+        }
+    }
+}
+
+impl<I> Iterator for ValidatingIter<I>
+where
+    I: Iterator,
+    I::Item: AsRef<[u8]>,
+{
+    type Item = Result<SsbMessageValue>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let message = self.messages.next()?;
+        let message_bytes = message.as_ref();
+        let previous = self
+            .previous
+            .as_ref()
+            .map(|(previous_value, previous_key)| (previous_value, previous_key));
+
+        match validate_message_value_against_previous(
+            message_bytes,
+            previous,
+            &ValidationOptions::default(),
+        ) {
+            Ok(message_value) => {
+                let key = utils::multihash_from_bytes(message_bytes);
+                self.previous = Some((message_value.clone(), key));
+                Some(Ok(message_value))
+            }
+            Err(err) => {
+                self.failed = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// Extension trait adding [`validating`](ValidatingIterExt::validating) to iterators of
+/// JSON encoded message values.
+pub trait ValidatingIterExt: Iterator + Sized
+where
+    Self::Item: AsRef<[u8]>,
+{
+    /// Lazily validate the message values as a hash chain. See [`ValidatingIter`].
+    fn validating(self) -> ValidatingIter<Self> {
+        ValidatingIter::new(self)
+    }
+}
+
+impl<I> ValidatingIterExt for I
+where
+    I: Iterator,
+    I::Item: AsRef<[u8]>,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::iter::ValidatingIterExt;
+    use crate::test_data::{MESSAGE_VALUE_1, MESSAGE_VALUE_2, MESSAGE_VALUE_3};
+
+    #[test]
+    fn it_lazily_validates_a_feed() {
+        let messages = vec![
+            MESSAGE_VALUE_1.as_bytes().to_vec(),
+            MESSAGE_VALUE_2.as_bytes().to_vec(),
+            MESSAGE_VALUE_3.as_bytes().to_vec(),
+        ];
+        let sequences: Vec<u64> = messages
+            .into_iter()
+            .validating()
+            .map(|result| result.unwrap().sequence)
+            .collect();
+        assert_eq!(sequences, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn it_stops_after_the_first_invalid_message() {
+        let messages = [
+            MESSAGE_VALUE_1.as_bytes(),
+            MESSAGE_VALUE_3.as_bytes(),
+            MESSAGE_VALUE_2.as_bytes(),
+        ];
+        let mut iter = messages.iter().validating();
+        assert!(iter.next().unwrap().is_ok());
+        match iter.next() {
+            Some(Err(Error::InvalidSequenceNumber {
+                message: _,
+                actual: 3,
+                expected: 2,
+            })) => {}
+            _ => panic!(),
+        }
+        assert!(iter.next().is_none());
+    }
+}
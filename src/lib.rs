@@ -18,24 +18,56 @@
 //!
 //! - the value of the `previous` field must be the hash of the previous message
 //! - the value of the `sequence` field must be 1 larger than the `sequence` of the previous
-//! message
+//!   message
 //!
 //! Other criteria which all messages must satisfy (unless they are being validated out-of-order):
 //!
 //! - the value of the `hash` field must be `sha256`
+//! - the `author` must be an ed25519 feed id with a 32-byte key, and the `signature` an ed25519
+//!   signature of 64 bytes
+//! - the value of the `timestamp` field must not be negative
+//! - the value of the `sequence` field must be at least 1
+//! - the value of the `previous` field must be a message hash (`%...sha256`) rather than a blob hash
 //! - the `author` must not change compared the the previous message
 //! - if the message includes a `key`, it must be the hash of the `value` of the message
 //! - message `value` fields must be in the order: `previous`, `author` or `sequence`, `author` or
-//! `sequence`, `timestamp`, `hash`, `content`, `signature`
-//! - the message `value` must not include extra (unexpected) fields
-//! - the value of the message `content` field must be encoded in canonical base64 and contain
-//! `.box` if it is a string (encrypted private message)
+//!   `sequence`, `timestamp`, `hash`, `content`, `signature`
+//! - the message `value` must not include extra (unexpected) fields, such as a `meta` wrapper
+//!   added by some historical tools; the first such field is reported as
+//!   `Error::UnexpectedMessageValueField`. Since the fields are covered by the hash and
+//!   signature, they cannot be dropped to make the message valid, but
+//!   [`message_value::validate_message_value_lenient`] can be used to inspect such messages
+//! - the message `content` must be an object or a string; arrays and other values are rejected as
+//!   invalid when the message is decoded
+//! - object `content` must have a `type` string between 3 and 52 UTF-16 code units long; this is
+//!   enforced when the message is decoded. The stricter limit of 3 to 52 UTF-8 bytes can be
+//!   enabled with [`validate_content_type_length`](options::ValidationOptions::validate_content_type_length)
+//! - numbers anywhere in the message (including in `content`) must be finite; `NaN`, `Infinity`
+//!   and numbers too large for an `f64` are rejected when the message is decoded
+//! - the value of the message `content` field must be encoded in canonical base64 and end with
+//!   `.box` or `.box2` if it is a string (encrypted private message)
 //! - the length of the serialized message `value` must not exceed 8192 UTF-16 code units
+//!   ([`MAX_MESSAGE_VALUE_UTF16_UNITS`](utils::MAX_MESSAGE_VALUE_UTF16_UNITS))
 //!
 //! All of the above criteria are validated by this library (either directly or via dependencies).
 //!
 //! You can check messages one by one or batch process a collection of them (uses
-//! [rayon](https://docs.rs/rayon/1.2.0/rayon/index.html) internally)
+//! [rayon](https://docs.rs/rayon/1.2.0/rayon/index.html) internally). The batch (`par_`)
+//! functions require the `parallel` feature, which is enabled by default.
+//! With the `async` feature (off by default), [`message::validate_feed_async`] runs the batch
+//! validation on the blocking thread pool of the `tokio` runtime, for use from async code.
+//! With the `serde_json` feature, message values which have already been decoded as a
+//! `serde_json::Value` can be validated with [`message_value::validate_message_value_from_json`].
+//! With the `flume` feature, the messages of a flumedb `OffsetLog` can be validated as a feed with
+//! [`flume::validate_offset_log`].
+//! Message values which arrive in arbitrary chunks of bytes (for example, from a network stream)
+//! can be validated as they arrive with [`stream::StreamValidator`].
+//!
+//! ## WebAssembly
+//!
+//! The crate builds for `wasm32-unknown-unknown` with the `parallel` feature disabled. The `wasm`
+//! feature provides entry points for use from JavaScript via `wasm-bindgen` in the `wasm`
+//! module, which report errors as strings.
 //!
 //! ## Out-of-Order (OOO) and Multi-Author Validation
 //!
@@ -56,6 +88,41 @@
 //! a previous message (except that the `previous` field must be present in the message in the
 //! correct order).
 //!
+//! In both cases, a message must be consistent about whether it is the first message of its feed:
+//! its `previous` must be `null` if, and only if, its `sequence` is 1.
+//!
+//! ## Signature Verification
+//!
+//! When the `verify` feature is enabled, the `verify` module provides functions which validate
+//! a message and verify its signature in a single pass.
+//!
+//! ## Bendy Butt
+//!
+//! Messages published in the [Bendy Butt](https://github.com/ssb-ngi-pointer/bendy-butt-spec)
+//! (`bbfeed-v1`) feed format used by metafeeds can be validated with the functions in the
+//! [`bendy_butt`] module. The criteria are analogous to those above, but the messages are
+//! bencoded rather than JSON encoded.
+//!
+//! ## Content
+//!
+//! The `content` of common message types (`post`, `contact`, `vote` and `about`) can be parsed
+//! into typed representations with [`content::validate_and_classify`].
+//! The references (`@`, `%` and `&`) in the `content` of any message can be collected while
+//! validating with [`content::validate_and_collect_links`].
+//!
+//! ## Gabby Grove
+//!
+//! Messages published in the Gabby Grove (`ggfeed-v1`) feed format can be validated with
+//! [`gabby_grove::validate_gabby_grove_message`]. Gabby Grove messages are CBOR encoded and only
+//! carry the hash and size of their content, so the content is checked against these when it is
+//! present.
+//!
+//! ## Fuzzing
+//!
+//! Validation must never panic, whatever the input. The `fuzz` directory contains a
+//! [`cargo fuzz`](https://github.com/rust-fuzz/cargo-fuzz) target (`cargo fuzz run validate`)
+//! built on [`fuzz::fuzz_validate`], which is available with the `fuzzing` feature.
+//!
 //! ## Benchmarks
 //!
 //! Benchmarking on a 2016 2 core i5 shows that batch processing  is ~1.6 times faster than processing
@@ -63,8 +130,25 @@
 //!
 //! Benchmarking on Android on a [One Plus 5T](https://en.wikipedia.org/wiki/OnePlus_5T) (8 core arm64)
 //! shows that batch processing is ~3.3 times faster.
+pub mod bendy_butt;
+pub mod content;
+pub mod context;
 pub mod error;
+#[cfg(feature = "flume")]
+pub mod flume;
+#[cfg(feature = "fuzzing")]
+pub mod fuzz;
+pub mod gabby_grove;
+pub mod iter;
 pub mod message;
 pub mod message_value;
+pub mod options;
+pub mod stream;
 pub mod test_data;
+#[cfg(feature = "test_util")]
+pub mod test_util;
 pub mod utils;
+#[cfg(feature = "verify")]
+pub mod verify;
+#[cfg(feature = "wasm")]
+pub mod wasm;
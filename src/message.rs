@@ -1,25 +1,53 @@
 //! Functions for validating messages in the form of `KVT` (`key`, `value`, `timestamp`).
-use rayon::prelude::*;
+use std::collections::BTreeMap;
+#[cfg(feature = "parallel")]
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "parallel")]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(feature = "parallel")]
+use rayon::{prelude::*, ThreadPool};
 use serde::{Deserialize, Serialize};
-use snafu::{ensure, OptionExt, ResultExt};
+use snafu::{ensure, IntoError, OptionExt, ResultExt};
 use ssb_legacy_msg_data::{
     json::{from_slice, to_vec},
     value::Value,
+    LegacyF64,
 };
 use ssb_multiformats::multihash::Multihash;
 
+#[cfg(feature = "parallel")]
+use crate::error::{AuthorNotAllowed, Cancelled, ErrorKind};
 use crate::error::{
-    ActualHashDidNotMatchKey, AuthorsDidNotMatch, InvalidMessage,
-    InvalidMessageCouldNotSerializeValue, InvalidMessageNoValue, InvalidPreviousMessage, Result,
+    AuthorsDidNotMatch, BrokenChain, Error, ForkedFeed, InvalidKeyFormat, InvalidMessage,
+    InvalidMessageCouldNotSerializeValue, InvalidMessageNoValue, InvalidPreviousMessage,
+    InvalidReceiveTimestamp, InvalidSequenceNumber, LeadingBom, PreviousKeyMismatch, Result,
+    SelfReferentialPrevious, SequenceOverflow,
 };
-use crate::message_value::{message_value_common_checks, SsbMessageValue};
-use crate::utils;
+use crate::message_value::{
+    check_continuity_with_summary, message_value_common_checks, unexpected_field_error,
+    SsbMessageValue,
+};
+use crate::options::ValidationOptions;
+use crate::utils::{self, LegacyHasher, MessageHasher};
 
 /// Data type representing a `key-value` message object, where the `key` is a hash of the `value`.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SsbMessage {
     pub key: Multihash,
     pub value: SsbMessageValue,
+    /// The time at which the message was received, if present.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<LegacyF64>,
+}
+
+/// The position of a validated message in its feed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidatedPosition {
+    /// The message is the first message of its feed.
+    First { key: Multihash },
+    /// The message continues its feed from the previous message.
+    Continuation { key: Multihash, sequence: u64 },
 }
 
 /// Validate an out-of-order message without checking the author.
@@ -27,12 +55,14 @@ pub struct SsbMessage {
 /// It expects the messages to be the JSON encoded message of shape: `{key: "", value: {...}}`
 ///
 /// This checks that:
+/// - the `key` is a message hash (`%...sha256`) rather than a blob hash
 /// - the _actual_ hash matches the hash claimed in `key`
+/// - the `previous` is not the _actual_ hash of the message itself
 /// - the message contains the correct fields
 /// - the message value fields are in the correct order
 /// - there are no unexpected top-level fields in the message
 /// - the hash signature is defined as `sha256`
-/// - the message `content` string is canonical base64
+/// - the message `content` string is canonical base64 with a `.box` or `.box2` suffix
 ///
 /// This does not check:
 /// - the signature (see ssb-verify-signatures which lets you to batch verification of signatures)
@@ -43,39 +73,19 @@ pub struct SsbMessage {
 pub fn validate_multi_author_message_hash_chain<T: AsRef<[u8]>>(message_bytes: T) -> Result<()> {
     let message_bytes = message_bytes.as_ref();
 
-    let message = from_slice::<SsbMessage>(message_bytes).context(InvalidMessage {
-        message: message_bytes.to_owned(),
-    })?;
-
-    let message_value = message.value;
-
-    message_value_common_checks(&message_value, None, message_bytes, None, false)?;
-
-    let verifiable_msg: Value = from_slice(message_bytes).context(InvalidMessage {
-        message: message_bytes.to_owned(),
-    })?;
-
-    // Get the value from the message as this is what was hashed
-    let verifiable_msg_value = match verifiable_msg {
-        Value::Object(ref o) => o.get("value").context(InvalidMessageNoValue)?,
-        _ => panic!(),
-    };
-
-    // Get the "value" from the message as bytes that we can hash.
-    let value_bytes =
-        to_vec(verifiable_msg_value, false).context(InvalidMessageCouldNotSerializeValue)?;
+    let message = parse_message(message_bytes)?;
 
-    let message_actual_multihash = utils::multihash_from_bytes(&value_bytes);
+    message_value_common_checks(
+        &message.value,
+        None,
+        message_bytes,
+        None,
+        false,
+        &ValidationOptions::default(),
+    )?;
 
-    // The hash of the "value" must match the claimed value stored in the "key"
-    ensure!(
-        message_actual_multihash == message.key,
-        ActualHashDidNotMatchKey {
-            message: message_bytes.to_owned(),
-            actual_hash: message_actual_multihash,
-            expected_hash: message.key,
-        }
-    );
+    message_value_matching_key(message_bytes, &message.key)?;
+    check_previous_is_not_self(&message, message_bytes)?;
 
     Ok(())
 }
@@ -86,12 +96,13 @@ pub fn validate_multi_author_message_hash_chain<T: AsRef<[u8]>>(message_bytes: T
 /// current and previous message.
 ///
 /// It expects the messages to be the JSON encoded message of shape: `{key: "", value: {...}}`
-pub fn par_validate_multi_author_message_hash_chain_of_feed<T: AsRef<[u8]>>(
-    messages: &[T],
-) -> Result<()>
+///
+/// An empty collection of messages is valid.
+#[cfg(feature = "parallel")]
+pub fn par_validate_multi_author_message_hash_chain_of_feed<T>(messages: &[T]) -> Result<()>
 where
     [T]: ParallelSlice<T>,
-    T: Sync,
+    T: AsRef<[u8]> + Sync,
 {
     messages
         .par_iter()
@@ -103,13 +114,34 @@ where
         .try_reduce(|| (), |_, _| Ok(()))
 }
 
+/// Batch validate a collection of first messages, each from a different feed.
+///
+/// It expects the messages to be the JSON encoded message of shape: `{key: "", value: {...}}`
+///
+/// Each message is validated as the first message of its feed (with a `sequence` of 1 and a
+/// `previous` of `null`), including the check that the _actual_ hash matches the claimed `key`.
+/// This is the typical workload when bootstrapping replication of many feeds at once. Since there
+/// is no dependency between the messages, each one is validated as an independent parallel task.
+#[cfg(feature = "parallel")]
+pub fn par_validate_first_messages<T>(messages: &[T]) -> Result<()>
+where
+    [T]: ParallelSlice<T>,
+    T: AsRef<[u8]> + Sync,
+{
+    messages
+        .par_iter()
+        .try_for_each(|msg| validate_message_hash_chain::<_, &[u8]>(msg.as_ref(), None))
+}
+
 /// Validate an out-of-order message.
 ///
 /// It expects the messages to be the JSON encoded message of shape: `{key: "", value: {...}}`
 ///
 /// This checks that:
 /// - the author has not changed
+/// - the `key` is a message hash (`%...sha256`) rather than a blob hash
 /// - the _actual_ hash matches the hash claimed in `key`
+/// - the `previous` is not the _actual_ hash of the message itself
 /// - the message contains the correct fields
 ///
 /// This does not check:
@@ -135,50 +167,30 @@ pub fn validate_ooo_message_hash_chain<T: AsRef<[u8]>, U: AsRef<[u8]>>(
         None => (None, None),
     };
 
-    let message = from_slice::<SsbMessage>(message_bytes).context(InvalidMessage {
-        message: message_bytes.to_owned(),
-    })?;
-
-    let message_value = message.value;
+    let message = parse_message(message_bytes)?;
 
-    message_value_common_checks(&message_value, None, message_bytes, None, false)?;
+    message_value_common_checks(
+        &message.value,
+        None,
+        message_bytes,
+        None,
+        false,
+        &ValidationOptions::default(),
+    )?;
 
     if let Some(previous_value) = previous_value.as_ref() {
         // The authors are not allowed to change in a feed.
         ensure!(
-            message_value.author == previous_value.author,
+            message.value.author == previous_value.author,
             AuthorsDidNotMatch {
                 previous_author: previous_value.author.clone(),
-                author: message_value.author
+                author: message.value.author
             }
         );
     }
 
-    let verifiable_msg: Value = from_slice(message_bytes).context(InvalidMessage {
-        message: message_bytes.to_owned(),
-    })?;
-
-    // Get the value from the message as this is what was hashed
-    let verifiable_msg_value = match verifiable_msg {
-        Value::Object(ref o) => o.get("value").context(InvalidMessageNoValue)?,
-        _ => panic!(),
-    };
-
-    // Get the "value" from the message as bytes that we can hash.
-    let value_bytes =
-        to_vec(verifiable_msg_value, false).context(InvalidMessageCouldNotSerializeValue)?;
-
-    let message_actual_multihash = utils::multihash_from_bytes(&value_bytes);
-
-    // The hash of the "value" must match the claimed value stored in the "key"
-    ensure!(
-        message_actual_multihash == message.key,
-        ActualHashDidNotMatchKey {
-            message: message_bytes.to_owned(),
-            actual_hash: message_actual_multihash,
-            expected_hash: message.key,
-        }
-    );
+    message_value_matching_key(message_bytes, &message.key)?;
+    check_previous_is_not_self(&message, message_bytes)?;
 
     Ok(())
 }
@@ -189,10 +201,16 @@ pub fn validate_ooo_message_hash_chain<T: AsRef<[u8]>, U: AsRef<[u8]>>(
 /// number.
 ///
 /// It expects the messages to be the JSON encoded message of shape: `{key: "", value: {...}}`
-pub fn par_validate_ooo_message_hash_chain_of_feed<T: AsRef<[u8]>>(messages: &[T]) -> Result<()>
+///
+/// Every message must share the author of the first message in the collection, otherwise
+/// `Error::AuthorsDidNotMatch` is returned with the offending author.
+///
+/// An empty collection of messages is valid.
+#[cfg(feature = "parallel")]
+pub fn par_validate_ooo_message_hash_chain_of_feed<T>(messages: &[T]) -> Result<()>
 where
     [T]: ParallelSlice<T>,
-    T: Sync,
+    T: AsRef<[u8]> + Sync,
 {
     messages
         .par_iter()
@@ -203,13 +221,76 @@ where
                 if idx == 0 {
                     validate_ooo_message_hash_chain::<_, &[u8]>(msg.as_ref(), None)
                 } else {
-                    validate_ooo_message_hash_chain(msg.as_ref(), Some(messages[idx - 1].as_ref()))
+                    // Since the messages are out-of-order, check against the first message
+                    // rather than the adjacent one.
+                    validate_ooo_message_hash_chain(msg.as_ref(), Some(messages[0].as_ref()))
                 }
             },
         )
         .try_reduce(|| (), |_, _| Ok(()))
 }
 
+/// Batch validate a collection of out-of-order messages by a single author, as for
+/// [`par_validate_ooo_message_hash_chain_of_feed`], which together must form a contiguous range
+/// of sequence numbers.
+///
+/// It expects the messages to be the JSON encoded message of shape: `{key: "", value: {...}}`
+///
+/// The messages may be in any order, and the range need not start at sequence 1. A gap in the
+/// range is returned as `Error::InvalidSequenceNumber`, for the message after the gap, and two
+/// messages with the same sequence number as `Error::ForkedFeed`. This is useful to confirm that
+/// a collection gathered as a complete feed (or a complete part of one) has no missing messages.
+/// The previous message hashes are not checked; see [`sort_and_validate`] for that.
+///
+/// An empty collection of messages is valid.
+///
+/// # Example
+///```
+///use ssb_validate::message::validate_ooo_batch_dense;
+///use ssb_validate::test_data::{MESSAGE_1, MESSAGE_2, MESSAGE_3};
+///let messages = [MESSAGE_3.as_bytes(), MESSAGE_1.as_bytes(), MESSAGE_2.as_bytes()];
+///assert!(validate_ooo_batch_dense(&messages).is_ok());
+///
+///let messages = [MESSAGE_3.as_bytes(), MESSAGE_1.as_bytes()];
+///assert!(validate_ooo_batch_dense(&messages).is_err());
+///```
+#[cfg(feature = "parallel")]
+pub fn validate_ooo_batch_dense<T>(messages: &[T]) -> Result<()>
+where
+    [T]: ParallelSlice<T>,
+    T: AsRef<[u8]> + Sync,
+{
+    par_validate_ooo_message_hash_chain_of_feed(messages)?;
+
+    let mut sequences = messages
+        .par_iter()
+        .enumerate()
+        .map(|(idx, msg)| parse_message(msg.as_ref()).map(|msg| (msg.value.sequence, idx)))
+        .collect::<Result<Vec<_>>>()?;
+    sequences.sort_unstable();
+
+    for pair in sequences.windows(2) {
+        let (previous_seq, _) = pair[0];
+        let (sequence, idx) = pair[1];
+        ensure!(
+            sequence != previous_seq,
+            ForkedFeed {
+                previous_seq: previous_seq - 1
+            }
+        );
+        ensure!(
+            sequence == previous_seq + 1,
+            InvalidSequenceNumber {
+                message: messages[idx].as_ref().to_owned(),
+                actual: sequence,
+                expected: previous_seq + 1,
+            }
+        );
+    }
+
+    Ok(())
+}
+
 /// Batch validate a collection of messages, all by the same author, ordered by ascending sequence
 /// number, with no missing messages.
 ///
@@ -218,6 +299,9 @@ where
 /// This will mainly be useful during replication. Collect all the latest messages from a feed you're
 /// replicating and batch validate all the messages at once.
 ///
+/// An empty collection of messages is valid (whatever the `previous`); callers for which an empty
+/// batch is an error must check for it before validating.
+///
 /// # Example
 ///```
 ///use ssb_validate::message::par_validate_message_hash_chain_of_feed;
@@ -267,14 +351,109 @@ where
 /// let result = par_validate_message_hash_chain_of_feed::<_, &[u8]>(&messages, None);
 /// assert!(result.is_ok());
 ///```
-pub fn par_validate_message_hash_chain_of_feed<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+#[cfg(feature = "parallel")]
+pub fn par_validate_message_hash_chain_of_feed<T, U>(
+    messages: &[T],
+    previous: Option<U>,
+) -> Result<()>
+where
+    [T]: ParallelSlice<T>,
+    T: AsRef<[u8]> + Sync,
+    U: AsRef<[u8]> + Sync + Send,
+{
+    par_validate_message_hash_chain_of_feed_with_options(
+        messages,
+        previous,
+        &ValidationOptions::default(),
+    )
+}
+
+/// Batch validate a collection of messages, all by the same author, ordered by ascending sequence
+/// number, with no missing messages, using the given thread pool rather than the global pool.
+///
+/// This performs the same checks as [`par_validate_message_hash_chain_of_feed`]. Running the
+/// validation on a dedicated pool bounds the number of threads it occupies.
+///
+/// ## Example
+///```
+///use ssb_validate::message::par_validate_message_hash_chain_of_feed_in_pool;
+///use ssb_validate::test_data::{MESSAGE_1, MESSAGE_2};
+///
+///let pool = rayon::ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+///let messages = [MESSAGE_1.as_bytes(), MESSAGE_2.as_bytes()];
+///let result = par_validate_message_hash_chain_of_feed_in_pool::<_, &[u8]>(&pool, &messages, None);
+///assert!(result.is_ok());
+///```
+#[cfg(feature = "parallel")]
+pub fn par_validate_message_hash_chain_of_feed_in_pool<T, U>(
+    pool: &ThreadPool,
+    messages: &[T],
+    previous: Option<U>,
+) -> Result<()>
+where
+    [T]: ParallelSlice<T>,
+    T: AsRef<[u8]> + Sync,
+    U: AsRef<[u8]> + Sync + Send,
+{
+    pool.install(|| par_validate_message_hash_chain_of_feed(messages, previous))
+}
+
+/// Batch validate a collection of messages, all by the same author, ordered by ascending sequence
+/// number, with no missing messages, performing the optional checks enabled in `options`.
+///
+/// See [`par_validate_message_hash_chain_of_feed`].
+#[cfg(feature = "parallel")]
+pub fn par_validate_message_hash_chain_of_feed_with_options<T, U>(
+    messages: &[T],
+    previous: Option<U>,
+    options: &ValidationOptions,
+) -> Result<()>
+where
+    [T]: ParallelSlice<T>,
+    T: AsRef<[u8]> + Sync,
+    U: AsRef<[u8]> + Sync + Send,
+{
+    messages
+        .par_iter()
+        .enumerate()
+        .try_fold(
+            || (),
+            |_, (idx, msg)| {
+                // Only the key of `previous` is checked against its hash here; the keys of the
+                // messages of the batch are checked as each of them is validated.
+                if idx == 0 {
+                    let prev = previous.as_ref().map(|prev| prev.as_ref());
+                    validate_message_hash_chain_with_options(msg.as_ref(), prev, options)
+                } else {
+                    validate_message_hash_chain_in_batch(
+                        msg.as_ref(),
+                        messages[idx - 1].as_ref(),
+                        options,
+                    )
+                    .map(|_| ())
+                }
+            },
+        )
+        .try_reduce(|| (), |_, _| Ok(()))
+}
+
+/// Batch validate a collection of messages, all by the same author, ordered by ascending sequence
+/// number, with no missing messages, stopping early when `cancel` is set.
+///
+/// This performs the same checks as [`par_validate_message_hash_chain_of_feed`]. The flag is
+/// checked before each message is validated; once it is set, the remaining messages are skipped
+/// and `Error::Cancelled` is returned (unless a validation error was found first). This allows a
+/// server to abort the validation of a large batch, for example when a client disconnects.
+#[cfg(feature = "parallel")]
+pub fn par_validate_message_hash_chain_of_feed_cancellable<T, U>(
     messages: &[T],
     previous: Option<U>,
+    cancel: &AtomicBool,
 ) -> Result<()>
 where
     [T]: ParallelSlice<T>,
-    T: Sync,
-    U: Sync + Send + Copy,
+    T: AsRef<[u8]> + Sync,
+    U: AsRef<[u8]> + Sync + Send,
 {
     messages
         .par_iter()
@@ -282,152 +461,1382 @@ where
         .try_fold(
             || (),
             |_, (idx, msg)| {
+                ensure!(!cancel.load(Ordering::Relaxed), Cancelled);
                 if idx == 0 {
-                    let prev = previous.map(|prev| prev.as_ref().to_owned());
+                    let prev = previous.as_ref().map(|prev| prev.as_ref());
                     validate_message_hash_chain(msg.as_ref(), prev)
                 } else {
-                    validate_message_hash_chain(msg.as_ref(), Some(messages[idx - 1].as_ref()))
+                    validate_message_hash_chain_in_batch(
+                        msg.as_ref(),
+                        messages[idx - 1].as_ref(),
+                        &ValidationOptions::default(),
+                    )
+                    .map(|_| ())
                 }
             },
         )
         .try_reduce(|| (), |_, _| Ok(()))
 }
 
-/// Validate a message in relation to the previous message.
+/// Batch validate a collection of messages, all by the same author, ordered by ascending sequence
+/// number, with no missing messages, rejecting messages whose author is not in `allowed_authors`.
 ///
-/// It expects the messages to be the JSON encoded message of shape: `{key: "", value: {...}}`
+/// The author of each message is checked before any other validation is performed (notably before
+/// the message is hashed), failing with `Error::AuthorNotAllowed` if it is not in the set. When
+/// `allowed_authors` is `None` this is the same as [`par_validate_message_hash_chain_of_feed`].
+#[cfg(feature = "parallel")]
+pub fn par_validate_message_hash_chain_of_feed_with_allowed_authors<T, U>(
+    messages: &[T],
+    previous: Option<U>,
+    allowed_authors: Option<&HashSet<String>>,
+) -> Result<()>
+where
+    [T]: ParallelSlice<T>,
+    T: AsRef<[u8]> + Sync,
+    U: AsRef<[u8]> + Sync + Send,
+{
+    messages
+        .par_iter()
+        .enumerate()
+        .try_fold(
+            || (),
+            |_, (idx, msg)| {
+                if let Some(allowed_authors) = allowed_authors {
+                    check_author_allowed(msg.as_ref(), allowed_authors)?;
+                }
+                if idx == 0 {
+                    let prev = previous.as_ref().map(|prev| prev.as_ref());
+                    validate_message_hash_chain(msg.as_ref(), prev)
+                } else {
+                    validate_message_hash_chain_in_batch(
+                        msg.as_ref(),
+                        messages[idx - 1].as_ref(),
+                        &ValidationOptions::default(),
+                    )
+                    .map(|_| ())
+                }
+            },
+        )
+        .try_reduce(|| (), |_, _| Ok(()))
+}
+
+/// Check that the author of a message is in `allowed_authors`, decoding only the `author` (and
+/// `sequence`) of the message.
+#[cfg(feature = "parallel")]
+fn check_author_allowed(message_bytes: &[u8], allowed_authors: &HashSet<String>) -> Result<()> {
+    let (author, _) = utils::peek_author_sequence(message_bytes)?;
+    ensure!(
+        allowed_authors.contains(&author),
+        AuthorNotAllowed {
+            message: message_bytes.to_owned(),
+            author
+        }
+    );
+
+    Ok(())
+}
+
+/// Batch validate a collection of messages, all by the same author, ordered by ascending sequence
+/// number, with no missing messages, returning a [`FeedDigest`](utils::FeedDigest) of their keys.
 ///
-/// This checks that:
-/// - the sequence starts at one if it's the first message
-/// - the previous is correctly set to null if it's the first message
-/// - the sequence increments correctly
-/// - the author has not changed
-/// - the feed is not forked
-/// - the _actual_ hash matches the hash claimed in `key`
+/// This performs the same checks as [`par_validate_message_hash_chain_of_feed`]. Two peers can
+/// compare the returned digests to check that their replicas of the feed agree.
+#[cfg(feature = "parallel")]
+pub fn par_validate_message_hash_chain_of_feed_digest<T, U>(
+    messages: &[T],
+    previous: Option<U>,
+) -> Result<[u8; 32]>
+where
+    [T]: ParallelSlice<T>,
+    T: AsRef<[u8]> + Sync,
+    U: AsRef<[u8]> + Sync + Send,
+{
+    let options = ValidationOptions::default();
+    // Collecting a parallel iterator preserves the order of the messages.
+    let keys = messages
+        .par_iter()
+        .enumerate()
+        .map(|(idx, msg)| {
+            let (message, _) = if idx == 0 {
+                let prev = previous.as_ref().map(|prev| prev.as_ref());
+                validate_message_hash_chain_returning_value(msg.as_ref(), prev, &options)?
+            } else {
+                validate_message_hash_chain_in_batch(
+                    msg.as_ref(),
+                    messages[idx - 1].as_ref(),
+                    &options,
+                )?
+            };
+            Ok(message.key)
+        })
+        .collect::<Result<Vec<Multihash>>>()?;
+
+    let mut digest = utils::FeedDigest::new();
+    for key in &keys {
+        digest.update(key);
+    }
+
+    Ok(digest.finalize())
+}
+
+/// Batch validate a collection of messages, all by the same author, ordered by ascending sequence
+/// number, with no missing messages, returning the key of the last message (the tip).
 ///
-/// This does not check:
-/// - the signature. See ssb-verify-signatures which lets you to batch verification of signatures.
+/// This performs the same checks as [`par_validate_message_hash_chain_of_feed`]. The returned key
+/// (or `None`, if there are no messages) can be stored as the `previous` for validating the next
+/// batch of the feed, without hashing the last message again.
 ///
-/// `previous_msg_bytes` will be `None` only when `message_bytes` is the first message by that author.
+/// ## Example
+///```
+///use ssb_validate::message::par_validate_message_hash_chain_of_feed_returning_tip;
+///use ssb_validate::test_data::{MESSAGE_1, MESSAGE_2};
 ///
-/// # Example
+///let messages = [MESSAGE_2.as_bytes()];
+///let tip = par_validate_message_hash_chain_of_feed_returning_tip(&messages, Some(MESSAGE_1))
+///    .unwrap()
+///    .unwrap();
+///assert_eq!(
+///    tip.to_legacy_string(),
+///    "%kLWDux4wCG+OdQWAHnpBGzGlCehqMLfgLbzlKCvgesU=.sha256"
+///);
 ///```
-///use ssb_validate::message::validate_message_hash_chain;
-///let valid_message_1 = r##"{
-///  "key": "%/v5mCnV/kmnVtnF3zXtD4tbzoEQo4kRq/0d/bgxP1WI=.sha256",
-///  "value": {
-///    "previous": null,
-///    "author": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
-///    "sequence": 1,
-///    "timestamp": 1470186877575,
-///    "hash": "sha256",
-///    "content": {
-///      "type": "about",
-///      "about": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
-///      "name": "Piet"
-///    },
-///    "signature": "QJKWui3oyK6r5dH13xHkEVFhfMZDTXfK2tW21nyfheFClSf69yYK77Itj1BGcOimZ16pj9u3tMArLUCGSscqCQ==.sig.ed25519"
-///  },
-///  "timestamp": 1571140551481
-///}"##;
-///let valid_message_2 = r##"{
-///  "key": "%kLWDux4wCG+OdQWAHnpBGzGlCehqMLfgLbzlKCvgesU=.sha256",
-///  "value": {
-///    "previous": "%/v5mCnV/kmnVtnF3zXtD4tbzoEQo4kRq/0d/bgxP1WI=.sha256",
-///    "author": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
-///    "sequence": 2,
-///    "timestamp": 1470187292812,
-///    "hash": "sha256",
-///    "content": {
-///      "type": "about",
-///      "about": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
-///      "image": {
-///        "link": "&MxwsfZoq7X6oqnEX/TWIlAqd6S+jsUA6T1hqZYdl7RM=.sha256",
-///        "size": 642763,
-///        "type": "image/png",
-///        "width": 512,
-///        "height": 512
-///      }
-///    },
-///    "signature": "j3C7Us3JDnSUseF4ycRB0dTMs0xC6NAriAFtJWvx2uyz0K4zSj6XL8YA4BVqv+AHgo08+HxXGrpJlZ3ADwNnDw==.sig.ed25519"
-///  },
-///  "timestamp": 1571140551485
-///}"##;
-/// let result = validate_message_hash_chain(valid_message_2.as_bytes(), Some(valid_message_1));
-/// assert!(result.is_ok());
+#[cfg(feature = "parallel")]
+pub fn par_validate_message_hash_chain_of_feed_returning_tip<T, U>(
+    messages: &[T],
+    previous: Option<U>,
+) -> Result<Option<Multihash>>
+where
+    [T]: ParallelSlice<T>,
+    T: AsRef<[u8]> + Sync,
+    U: AsRef<[u8]> + Sync + Send,
+{
+    let options = ValidationOptions::default();
+    let tip = messages
+        .par_iter()
+        .enumerate()
+        .map(|(idx, msg)| {
+            let (message, _) = if idx == 0 {
+                let prev = previous.as_ref().map(|prev| prev.as_ref());
+                validate_message_hash_chain_returning_value(msg.as_ref(), prev, &options)?
+            } else {
+                validate_message_hash_chain_in_batch(
+                    msg.as_ref(),
+                    messages[idx - 1].as_ref(),
+                    &options,
+                )?
+            };
+            Ok(Some((idx, message.key)))
+        })
+        // Messages are validated out of order, so keep the key with the highest index.
+        .try_reduce(
+            || None,
+            |a, b| match (a, b) {
+                (Some(a), Some(b)) => Ok(Some(if a.0 > b.0 { a } else { b })),
+                (a, b) => Ok(a.or(b)),
+            },
+        )?;
+
+    Ok(tip.map(|(_, key)| key))
+}
+
+/// Batch validate a complete feed of messages held as owned `String`s, starting from the first
+/// message by the author.
+///
+/// This is [`par_validate_message_hash_chain_of_feed`] with a `previous` of `None`, saving the
+/// type hint that would otherwise be needed.
+///
+/// ## Example
 ///```
-pub fn validate_message_hash_chain<T: AsRef<[u8]>, U: AsRef<[u8]>>(
-    message_bytes: T,
+///use ssb_validate::message::validate_feed_strings;
+///use ssb_validate::test_data::{MESSAGE_1, MESSAGE_2};
+///
+///let messages = vec![MESSAGE_1.to_string(), MESSAGE_2.to_string()];
+///assert!(validate_feed_strings(&messages).is_ok());
+///```
+#[cfg(feature = "parallel")]
+pub fn validate_feed_strings(messages: &[String]) -> Result<()> {
+    par_validate_message_hash_chain_of_feed::<_, &[u8]>(messages, None)
+}
+
+/// Batch validate a complete feed of messages held as owned byte vectors, starting from the
+/// first message by the author.
+///
+/// This is [`par_validate_message_hash_chain_of_feed`] with a `previous` of `None`, saving the
+/// type hint that would otherwise be needed.
+#[cfg(feature = "parallel")]
+pub fn validate_feed_bytes(messages: &[Vec<u8>]) -> Result<()> {
+    par_validate_message_hash_chain_of_feed::<_, &[u8]>(messages, None)
+}
+
+/// Batch validate a complete feed of messages from async code, starting from the first message by
+/// the author.
+///
+/// The validation is performed by [`validate_feed_bytes`] on the blocking thread pool of the
+/// `tokio` runtime (see `tokio::task::spawn_blocking`), so that the CPU-bound work does not stall
+/// the async executor. Must be called from within a `tokio` runtime. Returns `Error::Cancelled` if
+/// the runtime is shut down before the validation completes.
+#[cfg(feature = "async")]
+pub async fn validate_feed_async(messages: Vec<Vec<u8>>) -> Result<()> {
+    let result = tokio::task::spawn_blocking(move || validate_feed_bytes(&messages)).await;
+
+    match result {
+        Ok(result) => result,
+        Err(err) => match err.try_into_panic() {
+            Ok(panic) => std::panic::resume_unwind(panic),
+            Err(_) => Cancelled.fail(),
+        },
+    }
+}
+
+/// Summary of the batch validation of a feed (see
+/// [`par_validate_message_hash_chain_of_feed_summary`]).
+#[cfg(feature = "parallel")]
+#[derive(Debug)]
+pub struct BatchSummary {
+    /// The number of messages, from the start of the collection, which are valid.
+    pub validated: usize,
+    /// The index and error of the first invalid message, if any.
+    pub first_error: Option<(usize, Error)>,
+    /// The author of the last valid message, if any.
+    pub author: Option<String>,
+    /// The sequence number of the last valid message, or 0 if there is none.
+    pub highest_sequence: u64,
+}
+
+/// Batch validate a collection of messages, all by the same author, ordered by ascending sequence
+/// number, with no missing messages, summarising the result.
+///
+/// This performs the same checks as [`par_validate_message_hash_chain_of_feed`]. Rather than
+/// returning only the first error, it reports how many messages were valid before the first
+/// invalid message, along with the author and sequence number of the last valid message.
+/// Validation still stops early: messages after the first invalid message are not validated.
+#[cfg(feature = "parallel")]
+pub fn par_validate_message_hash_chain_of_feed_summary<T, U>(
+    messages: &[T],
+    previous: Option<U>,
+) -> BatchSummary
+where
+    [T]: ParallelSlice<T>,
+    T: AsRef<[u8]> + Sync,
+    U: AsRef<[u8]> + Sync + Send,
+{
+    let first_error = messages
+        .par_iter()
+        .enumerate()
+        .find_map_first(|(idx, msg)| {
+            let result = if idx == 0 {
+                let prev = previous.as_ref().map(|prev| prev.as_ref());
+                validate_message_hash_chain(msg.as_ref(), prev)
+            } else {
+                validate_message_hash_chain_in_batch(
+                    msg.as_ref(),
+                    messages[idx - 1].as_ref(),
+                    &ValidationOptions::default(),
+                )
+                .map(|_| ())
+            };
+            result.err().map(|err| (idx, err))
+        });
+
+    let validated = first_error.as_ref().map_or(messages.len(), |(idx, _)| *idx);
+
+    // The last valid message has already been parsed successfully, so this cannot fail.
+    let (author, highest_sequence) = match validated.checked_sub(1) {
+        Some(last) => match utils::peek_author_sequence(messages[last].as_ref()) {
+            Ok((author, sequence)) => (Some(author), sequence),
+            Err(_) => (None, 0),
+        },
+        None => (None, 0),
+    };
+
+    BatchSummary {
+        validated,
+        first_error,
+        author,
+        highest_sequence,
+    }
+}
+
+/// Batch validate a collection of messages, all by the same author, ordered by ascending sequence
+/// number, with no missing messages, returning the length of the longest valid prefix.
+///
+/// This performs the same checks as [`par_validate_message_hash_chain_of_feed`]. `Ok(n)` means
+/// that all `n` messages are valid. `Err((n, e))` means that the first `n` messages are valid and
+/// the message at index `n` failed validation with `e`, so the first `n` messages can safely be
+/// persisted.
+///
+/// ## Example
+///```
+///use ssb_validate::message::par_validate_message_hash_chain_of_feed_prefix;
+///use ssb_validate::test_data::{MESSAGE_1, MESSAGE_2, MESSAGE_3};
+///
+///// The second message is missing, so only the first message is valid.
+///let messages = [MESSAGE_1.as_bytes(), MESSAGE_3.as_bytes()];
+///let result = par_validate_message_hash_chain_of_feed_prefix::<_, &[u8]>(&messages, None);
+///assert!(matches!(result, Err((1, _))));
+///```
+#[cfg(feature = "parallel")]
+pub fn par_validate_message_hash_chain_of_feed_prefix<T, U>(
+    messages: &[T],
+    previous: Option<U>,
+) -> std::result::Result<usize, (usize, Error)>
+where
+    [T]: ParallelSlice<T>,
+    T: AsRef<[u8]> + Sync,
+    U: AsRef<[u8]> + Sync + Send,
+{
+    let summary = par_validate_message_hash_chain_of_feed_summary(messages, previous);
+    match summary.first_error {
+        Some(first_error) => Err(first_error),
+        None => Ok(summary.validated),
+    }
+}
+
+/// Statistics of the batch validation of a feed (see
+/// [`par_validate_message_hash_chain_of_feed_stats`]).
+#[cfg(feature = "parallel")]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BatchStats {
+    /// The number of messages which are valid.
+    pub validated: usize,
+    /// The number of invalid messages, by kind of error.
+    pub errors: HashMap<ErrorKind, usize>,
+}
+
+/// Batch validate a collection of messages, all by the same author, ordered by ascending sequence
+/// number, with no missing messages, tallying the errors encountered.
+///
+/// This performs the same checks as [`par_validate_message_hash_chain_of_feed`], but does not stop
+/// at the first invalid message: every message is validated against the message before it in the
+/// collection (or `previous`, for the first message), and the number of valid messages and of each
+/// kind of error is returned. An invalid message therefore also counts against the message which
+/// follows it if it breaks the chain (for example, a forked message yields a second `ForkedFeed`).
+///
+/// ## Example
+///```
+///use ssb_validate::error::ErrorKind;
+///use ssb_validate::message::par_validate_message_hash_chain_of_feed_stats;
+///use ssb_validate::test_data::{MESSAGE_1, MESSAGE_2, MESSAGE_3};
+///
+///// The second message is missing, so the third message is out of sequence.
+///let messages = [MESSAGE_1.as_bytes(), MESSAGE_3.as_bytes()];
+///let stats = par_validate_message_hash_chain_of_feed_stats::<_, &[u8]>(&messages, None);
+///assert_eq!(stats.validated, 1);
+///assert_eq!(stats.errors.get(&ErrorKind::InvalidSequenceNumber), Some(&1));
+///```
+#[cfg(feature = "parallel")]
+pub fn par_validate_message_hash_chain_of_feed_stats<T, U>(
+    messages: &[T],
+    previous: Option<U>,
+) -> BatchStats
+where
+    [T]: ParallelSlice<T>,
+    T: AsRef<[u8]> + Sync,
+    U: AsRef<[u8]> + Sync + Send,
+{
+    messages
+        .par_iter()
+        .enumerate()
+        .map(|(idx, msg)| {
+            if idx == 0 {
+                let prev = previous.as_ref().map(|prev| prev.as_ref());
+                validate_message_hash_chain(msg.as_ref(), prev)
+            } else {
+                validate_message_hash_chain_in_batch(
+                    msg.as_ref(),
+                    messages[idx - 1].as_ref(),
+                    &ValidationOptions::default(),
+                )
+                .map(|_| ())
+            }
+        })
+        .fold(BatchStats::default, |mut stats, result| {
+            match result {
+                Ok(()) => stats.validated += 1,
+                Err(err) => *stats.errors.entry(err.kind()).or_insert(0) += 1,
+            }
+            stats
+        })
+        .reduce(BatchStats::default, |mut stats, other| {
+            stats.validated += other.validated;
+            for (kind, count) in other.errors {
+                *stats.errors.entry(kind).or_insert(0) += count;
+            }
+            stats
+        })
+}
+
+/// Validate a collection of messages and detect forks among them.
+///
+/// It expects the messages to be the JSON encoded message of shape: `{key: "", value: {...}}`
+///
+/// Each message is first validated in isolation (see
+/// [`validate_multi_author_message_hash_chain`]). The messages are then grouped by `author`,
+/// `sequence` and `previous`, and every `previous` key which has more than one distinct child is
+/// reported along with the keys of those children. Unlike the fork check of
+/// [`validate_message_hash_chain`], this detects forks between messages which are not adjacent in
+/// the input, and between messages of different feeds in the same batch.
+///
+/// First messages (with a `previous` of `null`) have no previous key and are not reported.
+pub fn detect_forks<T: AsRef<[u8]>>(messages: &[T]) -> Result<Vec<(Multihash, Vec<Multihash>)>> {
+    let mut children: BTreeMap<(String, u64, Multihash), Vec<Multihash>> = BTreeMap::new();
+
+    for msg in messages {
+        let message_bytes = msg.as_ref();
+        validate_multi_author_message_hash_chain(message_bytes)?;
+
+        let message = parse_message(message_bytes)?;
+        if let Some(previous) = message.value.previous {
+            let keys = children
+                .entry((message.value.author, message.value.sequence, previous))
+                .or_default();
+            if !keys.contains(&message.key) {
+                keys.push(message.key);
+            }
+        }
+    }
+
+    let forks = children
+        .into_iter()
+        .filter(|(_, keys)| keys.len() > 1)
+        .map(|((_, _, previous), keys)| (previous, keys))
+        .collect();
+
+    Ok(forks)
+}
+
+/// Validate an unordered collection of messages by a single author, returning the indices of the
+/// messages in feed order.
+///
+/// It expects the messages to be the JSON encoded message of shape: `{key: "", value: {...}}`
+///
+/// Each message is first validated on its own (as by [`validate_multi_author_message_hash_chain`]).
+/// The feed is then rebuilt by following the `previous` links from the first message, validating
+/// each message against its predecessor (as by [`validate_message_hash_chain`]). Two messages with
+/// the same predecessor are reported as `Error::ForkedFeed`, and messages which cannot be reached
+/// from the first message (because it or an intermediate message is missing) as
+/// `Error::BrokenChain`. This is useful after pulling the messages of a feed out of order.
+///
+/// # Example
+///```
+///use ssb_validate::message::sort_and_validate;
+///use ssb_validate::test_data::{MESSAGE_1, MESSAGE_2, MESSAGE_3};
+///let messages = [MESSAGE_3.as_bytes(), MESSAGE_1.as_bytes(), MESSAGE_2.as_bytes()];
+///assert_eq!(sort_and_validate(&messages).unwrap(), vec![1, 2, 0]);
+///```
+pub fn sort_and_validate<T: AsRef<[u8]>>(messages: &[T]) -> Result<Vec<usize>> {
+    let mut first = Vec::new();
+    let mut children: BTreeMap<Multihash, Vec<usize>> = BTreeMap::new();
+    let mut keys = Vec::with_capacity(messages.len());
+
+    for (idx, msg) in messages.iter().enumerate() {
+        let message_bytes = msg.as_ref();
+        validate_multi_author_message_hash_chain(message_bytes)?;
+
+        let message = parse_message(message_bytes)?;
+        match message.value.previous {
+            Some(previous) => children.entry(previous).or_default().push(idx),
+            None => first.push(idx),
+        }
+        keys.push(message.key);
+    }
+    utils::assert_single_author(messages)?;
+
+    let mut order = Vec::with_capacity(messages.len());
+    let mut next = match first[..] {
+        [] => None,
+        [idx] => {
+            validate_message_hash_chain::<_, &[u8]>(messages[idx].as_ref(), None)?;
+            Some(idx)
+        }
+        _ => return ForkedFeed { previous_seq: 0u64 }.fail(),
+    };
+    while let Some(idx) = next {
+        order.push(idx);
+        next = match children.get(&keys[idx]).map(|children| &children[..]) {
+            None => None,
+            Some([child]) => {
+                validate_message_hash_chain_in_batch(
+                    messages[*child].as_ref(),
+                    messages[idx].as_ref(),
+                    &ValidationOptions::default(),
+                )?;
+                Some(*child)
+            }
+            Some(_) => {
+                let message = parse_message(messages[idx].as_ref())?;
+                return ForkedFeed {
+                    previous_seq: message.value.sequence,
+                }
+                .fail();
+            }
+        };
+    }
+
+    ensure!(
+        order.len() == messages.len(),
+        BrokenChain {
+            unchained: messages.len() - order.len()
+        }
+    );
+
+    Ok(order)
+}
+
+/// Find which of several candidate previous messages a message correctly continues.
+///
+/// It expects the messages to be the JSON encoded message of shape: `{key: "", value: {...}}`
+///
+/// The message is first validated on its own (as by [`validate_multi_author_message_hash_chain`])
+/// and any error is returned as-is. Each candidate is then tried in turn as the previous message,
+/// performing the same checks as [`validate_message_hash_chain`], and the index of the first
+/// candidate which the message continues is returned. If no candidate matches, this returns
+/// `Error::ForkedFeed`. This is useful when resolving a suspected fork, where a message may have
+/// more than one possible predecessor.
+///
+/// # Example
+///```
+///use ssb_validate::message::validate_against_candidates;
+///use ssb_validate::test_data::{MESSAGE_1, MESSAGE_2, MESSAGE_3};
+///let candidates = [MESSAGE_1.as_bytes(), MESSAGE_2.as_bytes()];
+///assert_eq!(validate_against_candidates(MESSAGE_3, &candidates).unwrap(), 1);
+///```
+pub fn validate_against_candidates<T: AsRef<[u8]>>(
+    message_bytes: T,
+    candidates: &[&[u8]],
+) -> Result<usize> {
+    let message_bytes = message_bytes.as_ref();
+    validate_multi_author_message_hash_chain(message_bytes)?;
+
+    let position = candidates
+        .iter()
+        .position(|candidate| validate_message_hash_chain(message_bytes, Some(candidate)).is_ok());
+
+    match position {
+        Some(index) => Ok(index),
+        None => {
+            let message = parse_message(message_bytes)?;
+            ForkedFeed {
+                previous_seq: message.value.sequence.saturating_sub(1),
+            }
+            .fail()
+        }
+    }
+}
+
+/// Validate a collection of messages, all by the same author, ordered by descending sequence
+/// number (newest first), with no missing messages.
+///
+/// It expects the messages to be the JSON encoded message of shape: `{key: "", value: {...}}`
+///
+/// The last message in the slice is treated as the oldest. `previous` is the message which
+/// precedes the oldest message, or `None` if the oldest message is the first message of the feed.
+/// The messages are validated one at a time, from oldest to newest, performing the same checks
+/// as [`par_validate_message_hash_chain_of_feed`]. This is useful when paging backward through a
+/// feed, since the messages need not be reversed first.
+pub fn validate_message_hash_chain_of_feed_rev<T, U>(
+    messages: &[T],
+    previous: Option<U>,
+) -> Result<()>
+where
+    T: AsRef<[u8]>,
+    U: AsRef<[u8]>,
+{
+    for (idx, msg) in messages.iter().rev().enumerate() {
+        if idx == 0 {
+            let prev = previous.as_ref().map(|prev| prev.as_ref());
+            validate_message_hash_chain(msg.as_ref(), prev)?;
+        } else {
+            validate_message_hash_chain_in_batch(
+                msg.as_ref(),
+                messages[messages.len() - idx].as_ref(),
+                &ValidationOptions::default(),
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate a message in relation to the previous message.
+///
+/// It expects the messages to be the JSON encoded message of shape: `{key: "", value: {...}}`
+///
+/// This checks that:
+/// - the sequence starts at one if it's the first message
+/// - the previous is correctly set to null if it's the first message
+/// - the sequence increments correctly
+/// - the author has not changed
+/// - the feed is not forked
+/// - the `previous` is not the _actual_ hash of the message itself
+/// - the `key` is a message hash (`%...sha256`) rather than a blob hash
+/// - the _actual_ hash matches the hash claimed in `key`
+/// - the _actual_ hash of the previous message matches the hash claimed in its `key`
+///
+/// This does not check:
+/// - the signature. See ssb-verify-signatures which lets you to batch verification of signatures.
+///
+/// `previous_msg_bytes` will be `None` only when `message_bytes` is the first message by that author.
+///
+/// # Example
+///```
+///use ssb_validate::message::validate_message_hash_chain;
+///let valid_message_1 = r##"{
+///  "key": "%/v5mCnV/kmnVtnF3zXtD4tbzoEQo4kRq/0d/bgxP1WI=.sha256",
+///  "value": {
+///    "previous": null,
+///    "author": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+///    "sequence": 1,
+///    "timestamp": 1470186877575,
+///    "hash": "sha256",
+///    "content": {
+///      "type": "about",
+///      "about": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+///      "name": "Piet"
+///    },
+///    "signature": "QJKWui3oyK6r5dH13xHkEVFhfMZDTXfK2tW21nyfheFClSf69yYK77Itj1BGcOimZ16pj9u3tMArLUCGSscqCQ==.sig.ed25519"
+///  },
+///  "timestamp": 1571140551481
+///}"##;
+///let valid_message_2 = r##"{
+///  "key": "%kLWDux4wCG+OdQWAHnpBGzGlCehqMLfgLbzlKCvgesU=.sha256",
+///  "value": {
+///    "previous": "%/v5mCnV/kmnVtnF3zXtD4tbzoEQo4kRq/0d/bgxP1WI=.sha256",
+///    "author": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+///    "sequence": 2,
+///    "timestamp": 1470187292812,
+///    "hash": "sha256",
+///    "content": {
+///      "type": "about",
+///      "about": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+///      "image": {
+///        "link": "&MxwsfZoq7X6oqnEX/TWIlAqd6S+jsUA6T1hqZYdl7RM=.sha256",
+///        "size": 642763,
+///        "type": "image/png",
+///        "width": 512,
+///        "height": 512
+///      }
+///    },
+///    "signature": "j3C7Us3JDnSUseF4ycRB0dTMs0xC6NAriAFtJWvx2uyz0K4zSj6XL8YA4BVqv+AHgo08+HxXGrpJlZ3ADwNnDw==.sig.ed25519"
+///  },
+///  "timestamp": 1571140551485
+///}"##;
+/// let result = validate_message_hash_chain(valid_message_2.as_bytes(), Some(valid_message_1));
+/// assert!(result.is_ok());
+///```
+pub fn validate_message_hash_chain<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+    message_bytes: T,
+    previous_msg_bytes: Option<U>,
+) -> Result<()> {
+    validate_message_hash_chain_with_options(
+        message_bytes,
+        previous_msg_bytes,
+        &ValidationOptions::default(),
+    )
+}
+
+/// Check whether a message is valid in relation to the previous message.
+///
+/// This performs exactly the same checks as [`validate_message_hash_chain`], for callers which
+/// only need a yes or no answer. It never panics, whatever the bytes; note that `false` does not
+/// say why the message is invalid.
+pub fn is_valid_message_hash_chain<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+    message_bytes: T,
     previous_msg_bytes: Option<U>,
+) -> bool {
+    validate_message_hash_chain(message_bytes, previous_msg_bytes).is_ok()
+}
+
+/// Validate a message in relation to the previous message, performing the optional checks
+/// enabled in `options`.
+///
+/// See [`validate_message_hash_chain`].
+pub fn validate_message_hash_chain_with_options<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+    message_bytes: T,
+    previous_msg_bytes: Option<U>,
+    options: &ValidationOptions,
+) -> Result<()> {
+    validate_message_hash_chain_returning_value(message_bytes, previous_msg_bytes, options)?;
+
+    Ok(())
+}
+
+/// Validate a message in relation to the previous message, requiring it to be by
+/// `expected_author`.
+///
+/// It expects the messages to be the JSON encoded message of shape: `{key: "", value: {...}}`
+///
+/// The author is checked first, returning `Error::AuthorsDidNotMatch` (with `expected_author` as
+/// the `previous_author`) if it differs; otherwise this performs the same checks as
+/// [`validate_message_hash_chain`]. This is useful when replicating a particular feed, where a
+/// well-formed message from any other feed must not be accepted.
+///
+/// # Example
+///```
+///use ssb_validate::message::validate_message_hash_chain_for_author;
+///use ssb_validate::test_data::{MESSAGE_1, MESSAGE_2};
+///let author = "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519";
+///assert!(validate_message_hash_chain_for_author(MESSAGE_2, Some(MESSAGE_1), author).is_ok());
+///```
+pub fn validate_message_hash_chain_for_author<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+    message_bytes: T,
+    previous_msg_bytes: Option<U>,
+    expected_author: &str,
 ) -> Result<()> {
     let message_bytes = message_bytes.as_ref();
-    // msg seq is 1 larger than previous
-    let (previous_value, previous_key) = match previous_msg_bytes {
+    let message = parse_message(message_bytes)?;
+    ensure!(
+        message.value.author == expected_author,
+        AuthorsDidNotMatch {
+            previous_author: expected_author,
+            author: message.value.author
+        }
+    );
+
+    validate_message_hash_chain(message_bytes, previous_msg_bytes)
+}
+
+/// Validate a message in relation to the previous message, computing the key of the message with
+/// `hasher` rather than with the hashing convention of the classic network.
+///
+/// This performs exactly the same checks as [`validate_message_hash_chain`]; passing
+/// [`LegacyHasher`] is equivalent to calling it directly.
+///
+/// # Example
+///```
+///use ssb_validate::message::validate_message_hash_chain_with_hasher;
+///use ssb_validate::test_data::{MESSAGE_1, MESSAGE_2};
+///use ssb_validate::utils::LegacyHasher;
+///let result = validate_message_hash_chain_with_hasher(MESSAGE_2, Some(MESSAGE_1), &LegacyHasher);
+///assert!(result.is_ok());
+///```
+pub fn validate_message_hash_chain_with_hasher<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+    message_bytes: T,
+    previous_msg_bytes: Option<U>,
+    hasher: &dyn MessageHasher,
+) -> Result<()> {
+    validate_message_hash_chain_returning_value_with_hasher(
+        message_bytes,
+        previous_msg_bytes,
+        &ValidationOptions::default(),
+        hasher,
+    )?;
+
+    Ok(())
+}
+
+/// Validate a message in relation to the previous message, returning the parsed message on
+/// success.
+///
+/// This performs exactly the same checks as [`validate_message_hash_chain`] and is useful when the
+/// message is needed after validation (for example, to order messages by their receive
+/// `timestamp`), since it avoids parsing the message a second time.
+///
+/// # Example
+///```
+///use ssb_validate::message::validate_message_hash_chain_parsed;
+///use ssb_validate::test_data::MESSAGE_1;
+///let message = validate_message_hash_chain_parsed::<_, &[u8]>(MESSAGE_1, None).unwrap();
+///assert_eq!(message.value.sequence, 1);
+///assert_eq!(message.timestamp.map(f64::from), Some(1571140551481.0));
+///```
+pub fn validate_message_hash_chain_parsed<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+    message_bytes: T,
+    previous_msg_bytes: Option<U>,
+) -> Result<SsbMessage> {
+    let (message, _) = validate_message_hash_chain_returning_value(
+        message_bytes,
+        previous_msg_bytes,
+        &ValidationOptions::default(),
+    )?;
+
+    Ok(message)
+}
+
+/// Validate a message in relation to the previous message, returning the sequence expected of the
+/// next message in the feed.
+///
+/// This performs exactly the same checks as [`validate_message_hash_chain`]. The next sequence is
+/// one greater than the `sequence` of the message, or `Error::SequenceOverflow` if the message
+/// has the largest possible sequence. See [`validate_message_hash_chain_position`] for the `key`
+/// of the message.
+///
+/// # Example
+///```
+///use ssb_validate::message::validate_message_hash_chain_next_sequence;
+///use ssb_validate::test_data::{MESSAGE_1, MESSAGE_2};
+///let next_sequence = validate_message_hash_chain_next_sequence(MESSAGE_2, Some(MESSAGE_1));
+///assert_eq!(next_sequence.unwrap(), 3);
+///```
+pub fn validate_message_hash_chain_next_sequence<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+    message_bytes: T,
+    previous_msg_bytes: Option<U>,
+) -> Result<u64> {
+    let message_bytes = message_bytes.as_ref();
+    let (message, _) = validate_message_hash_chain_returning_value(
+        message_bytes,
+        previous_msg_bytes,
+        &ValidationOptions::default(),
+    )?;
+
+    message
+        .value
+        .sequence
+        .checked_add(1)
+        .context(SequenceOverflow {
+            message: message_bytes.to_owned(),
+        })
+}
+
+/// Validate the first message of a feed, returning its key.
+///
+/// It expects the messages to be the JSON encoded message of shape: `{key: "", value: {...}}`
+///
+/// This performs exactly the same checks as [`validate_message_hash_chain`] with no previous
+/// message: the `sequence` must be `1`, the `previous` must be `null` and the `key` must be the
+/// hash of the message `value`. The returned key is the anchor for validating the rest of the
+/// feed.
+///
+/// # Example
+///```
+///use ssb_validate::message::validate_first_message;
+///use ssb_validate::test_data::MESSAGE_1;
+///let key = validate_first_message(MESSAGE_1).unwrap();
+///assert_eq!(
+///    key.to_legacy_string(),
+///    "%/v5mCnV/kmnVtnF3zXtD4tbzoEQo4kRq/0d/bgxP1WI=.sha256"
+///);
+///```
+pub fn validate_first_message<T: AsRef<[u8]>>(message_bytes: T) -> Result<Multihash> {
+    let (message, _) = validate_message_hash_chain_returning_value::<_, &[u8]>(
+        message_bytes,
+        None,
+        &ValidationOptions::default(),
+    )?;
+
+    Ok(message.key)
+}
+
+/// Validate a message claimed to be the latest message (the tip) of its feed, returning its key.
+///
+/// It expects the messages to be the JSON encoded message of shape: `{key: "", value: {...}}`
+///
+/// The message is validated in isolation (see [`validate_multi_author_message_hash_chain`]), and
+/// its `sequence` must be `expected_sequence` (for example, the sequence advertised by a peer
+/// during replication), otherwise `Error::InvalidSequenceNumber` is returned. The returned key can
+/// then be used as the `previous` of the next message of the feed.
+///
+/// # Example
+///```
+///use ssb_validate::message::validate_tip;
+///use ssb_validate::test_data::MESSAGE_2;
+///let key = validate_tip(MESSAGE_2, 2).unwrap();
+///assert_eq!(
+///    key.to_legacy_string(),
+///    "%kLWDux4wCG+OdQWAHnpBGzGlCehqMLfgLbzlKCvgesU=.sha256"
+///);
+///assert!(validate_tip(MESSAGE_2, 3).is_err());
+///```
+pub fn validate_tip<T: AsRef<[u8]>>(message_bytes: T, expected_sequence: u64) -> Result<Multihash> {
+    let message_bytes = message_bytes.as_ref();
+    validate_multi_author_message_hash_chain(message_bytes)?;
+
+    let message = parse_message(message_bytes)?;
+    ensure!(
+        message.value.sequence == expected_sequence,
+        InvalidSequenceNumber {
+            message: message_bytes.to_owned(),
+            actual: message.value.sequence,
+            expected: expected_sequence,
+        }
+    );
+
+    Ok(message.key)
+}
+
+/// Validate a message in relation to the previous message, returning the position of the message
+/// in its feed.
+///
+/// This performs exactly the same checks as [`validate_message_hash_chain`]. A message validated
+/// without a previous message is the first message of its feed ([`ValidatedPosition::First`]),
+/// otherwise it continues the feed ([`ValidatedPosition::Continuation`]).
+///
+/// # Example
+///```
+///use ssb_validate::message::{validate_message_hash_chain_position, ValidatedPosition};
+///let valid_message_1 = r##"{
+///  "key": "%/v5mCnV/kmnVtnF3zXtD4tbzoEQo4kRq/0d/bgxP1WI=.sha256",
+///  "value": {
+///    "previous": null,
+///    "author": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+///    "sequence": 1,
+///    "timestamp": 1470186877575,
+///    "hash": "sha256",
+///    "content": {
+///      "type": "about",
+///      "about": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+///      "name": "Piet"
+///    },
+///    "signature": "QJKWui3oyK6r5dH13xHkEVFhfMZDTXfK2tW21nyfheFClSf69yYK77Itj1BGcOimZ16pj9u3tMArLUCGSscqCQ==.sig.ed25519"
+///  },
+///  "timestamp": 1571140551481
+///}"##;
+/// let position = validate_message_hash_chain_position::<_, &[u8]>(valid_message_1.as_bytes(), None);
+/// match position {
+///     Ok(ValidatedPosition::First { key: _ }) => {}
+///     _ => panic!(),
+/// }
+///```
+pub fn validate_message_hash_chain_position<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+    message_bytes: T,
+    previous_msg_bytes: Option<U>,
+) -> Result<ValidatedPosition> {
+    let (message, _) = validate_message_hash_chain_returning_value(
+        message_bytes,
+        previous_msg_bytes,
+        &ValidationOptions::default(),
+    )?;
+
+    // The checks for the previous message guarantee that `previous` is null if (and only if) the
+    // message was validated as the first message.
+    let position = match message.value.previous {
+        None => ValidatedPosition::First { key: message.key },
+        Some(_) => ValidatedPosition::Continuation {
+            key: message.key,
+            sequence: message.value.sequence,
+        },
+    };
+
+    Ok(position)
+}
+
+/// Validate a message in relation to the previous message, returning the parsed message and the
+/// `value` of the message in the generic form in which it was hashed (and signed).
+pub(crate) fn validate_message_hash_chain_returning_value<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+    message_bytes: T,
+    previous_msg_bytes: Option<U>,
+    options: &ValidationOptions,
+) -> Result<(SsbMessage, Value)> {
+    validate_message_hash_chain_returning_value_with_hasher(
+        message_bytes,
+        previous_msg_bytes,
+        options,
+        &LegacyHasher,
+    )
+}
+
+/// Validate a message in relation to the previous message, computing the key of the message with
+/// `hasher`, and return the parsed message and the `value` of the message.
+fn validate_message_hash_chain_returning_value_with_hasher<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+    message_bytes: T,
+    previous_msg_bytes: Option<U>,
+    options: &ValidationOptions,
+    hasher: &dyn MessageHasher,
+) -> Result<(SsbMessage, Value)> {
+    let previous = match previous_msg_bytes {
         Some(message) => {
-            let previous =
-                from_slice::<SsbMessage>(message.as_ref()).context(InvalidPreviousMessage {
-                    message: message.as_ref().to_owned(),
-                })?;
-            (Some(previous.value), Some(previous.key))
+            let message = message.as_ref();
+            let previous = parse_previous_message(message)?;
+            // The `previous` of the message is compared with the key of the previous message, so
+            // that key must itself be the hash of the previous message value.
+            check_previous_key(message, &previous, hasher)?;
+            Some(previous)
+        }
+        None => None,
+    };
+
+    validate_message_against_previous(message_bytes.as_ref(), previous, options, hasher)
+}
+
+/// Validate a message in relation to the message before it in the same batch, returning the
+/// parsed message and the `value` of the message.
+///
+/// This performs the same checks as [`validate_message_hash_chain_with_options`], except that the
+/// key of the previous message is not checked against the hash of its value. The previous message
+/// is itself an element of the batch, so its key is checked when it is validated in turn; checking
+/// it here too would parse and hash every message of the batch twice. The previous message of the
+/// first element of a batch is supplied by the caller, and must be validated with
+/// [`validate_message_hash_chain_returning_value`] instead.
+pub(crate) fn validate_message_hash_chain_in_batch(
+    message_bytes: &[u8],
+    previous_msg_bytes: &[u8],
+    options: &ValidationOptions,
+) -> Result<(SsbMessage, Value)> {
+    let previous = parse_previous_message(previous_msg_bytes)?;
+
+    validate_message_against_previous(message_bytes, Some(previous), options, &LegacyHasher)
+}
+
+/// Validate a message in relation to the parsed previous message, computing the key of the message
+/// with `hasher`, and return the parsed message and the `value` of the message.
+fn validate_message_against_previous(
+    message_bytes: &[u8],
+    previous: Option<SsbMessage>,
+    options: &ValidationOptions,
+    hasher: &dyn MessageHasher,
+) -> Result<(SsbMessage, Value)> {
+    // msg seq is 1 larger than previous
+    let (previous_value, previous_key) = match previous {
+        Some(previous) => (Some(previous.value), Some(previous.key)),
+        None => (None, None),
+    };
+
+    let message = parse_message(message_bytes)?;
+
+    check_receive_timestamp(&message, message_bytes, options)?;
+
+    message_value_common_checks(
+        &message.value,
+        previous_value.as_ref(),
+        message_bytes,
+        previous_key.as_ref(),
+        // run checks for previous msg
+        true,
+        options,
+    )?;
+
+    let message_value =
+        message_value_matching_key_with_hasher(message_bytes, &message.key, hasher)?;
+    check_previous_is_not_self(&message, message_bytes)?;
+
+    Ok((message, message_value))
+}
+
+/// Validate a message in relation to a checkpoint: the `key`, `sequence` and `author` of the
+/// previous message in the feed.
+///
+/// It expects the message to be the JSON encoded message of shape: `{key: "", value: {...}}`
+///
+/// This is useful when resuming replication of a feed, where the previous message has already been
+/// validated and only its key, sequence and author are at hand. It performs the same checks as
+/// [`validate_message_hash_chain`], except that the continuity of the feed is checked against the
+/// checkpoint:
+/// - the `previous` of the message matches `previous_key`
+/// - the `sequence` of the message is one greater than `previous_seq`
+/// - the `author` of the message matches `previous_author`
+///
+/// Since the timestamp of the previous message is unknown,
+/// [`enforce_monotonic_timestamps`](crate::options::ValidationOptions::enforce_monotonic_timestamps)
+/// cannot be applied here.
+///
+/// # Example
+///```
+///use ssb_multiformats::multihash::Multihash;
+///use ssb_validate::message::validate_message_hash_chain_from_checkpoint;
+///let valid_message_2 = r##"{
+///  "key": "%kLWDux4wCG+OdQWAHnpBGzGlCehqMLfgLbzlKCvgesU=.sha256",
+///  "value": {
+///    "previous": "%/v5mCnV/kmnVtnF3zXtD4tbzoEQo4kRq/0d/bgxP1WI=.sha256",
+///    "author": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+///    "sequence": 2,
+///    "timestamp": 1470187292812,
+///    "hash": "sha256",
+///    "content": {
+///      "type": "about",
+///      "about": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+///      "image": {
+///        "link": "&MxwsfZoq7X6oqnEX/TWIlAqd6S+jsUA6T1hqZYdl7RM=.sha256",
+///        "size": 642763,
+///        "type": "image/png",
+///        "width": 512,
+///        "height": 512
+///      }
+///    },
+///    "signature": "j3C7Us3JDnSUseF4ycRB0dTMs0xC6NAriAFtJWvx2uyz0K4zSj6XL8YA4BVqv+AHgo08+HxXGrpJlZ3ADwNnDw==.sig.ed25519"
+///  },
+///  "timestamp": 1571140551485
+///}"##;
+/// let (previous_key, _) =
+///     Multihash::from_legacy(b"%/v5mCnV/kmnVtnF3zXtD4tbzoEQo4kRq/0d/bgxP1WI=.sha256").unwrap();
+/// let result = validate_message_hash_chain_from_checkpoint(
+///     valid_message_2.as_bytes(),
+///     &previous_key,
+///     1,
+///     "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+/// );
+/// assert!(result.is_ok());
+///```
+pub fn validate_message_hash_chain_from_checkpoint<T: AsRef<[u8]>>(
+    message_bytes: T,
+    previous_key: &Multihash,
+    previous_seq: u64,
+    previous_author: &str,
+) -> Result<()> {
+    let message_bytes = message_bytes.as_ref();
+
+    let message = parse_message(message_bytes)?;
+
+    let message_value = message.value;
+
+    // perform common validation checks without `previous` message
+    message_value_common_checks(
+        &message_value,
+        None,
+        message_bytes,
+        None,
+        false,
+        &ValidationOptions::default(),
+    )?;
+
+    check_continuity_with_summary(
+        &message_value,
+        message_bytes,
+        previous_key,
+        previous_seq,
+        previous_author,
+    )?;
+
+    message_value_matching_key(message_bytes, &message.key)?;
+
+    Ok(())
+}
+
+/// The receive `timestamp` of a message must not be negative, if present (and if enabled).
+fn check_receive_timestamp(
+    message: &SsbMessage,
+    message_bytes: &[u8],
+    options: &ValidationOptions,
+) -> Result<()> {
+    if !options.validate_kvt_timestamp {
+        return Ok(());
+    }
+
+    if let Some(timestamp) = message.timestamp {
+        // `LegacyF64` already rules out non-finite values.
+        ensure!(
+            f64::from(timestamp) >= 0.0,
+            InvalidReceiveTimestamp {
+                message: message_bytes.to_owned()
+            }
+        );
+    }
+
+    Ok(())
+}
+
+/// The `key` of a message, ignoring all other fields.
+#[derive(Deserialize)]
+struct MessageKey {
+    key: String,
+}
+
+/// A message cannot follow itself; this would send naive traversal of the feed into a loop.
+///
+/// The `previous` of the message is compared with its `key`, so this must only be called once the
+/// `key` has been checked to be the hash of the message `value`.
+fn check_previous_is_not_self(message: &SsbMessage, message_bytes: &[u8]) -> Result<()> {
+    ensure!(
+        message.value.previous.as_ref() != Some(&message.key),
+        SelfReferentialPrevious {
+            message: message_bytes.to_owned(),
         }
+    );
 
-        None => (None, None),
-    };
+    Ok(())
+}
 
-    let message = from_slice::<SsbMessage>(message_bytes).context(InvalidMessage {
+/// Parse the previous message, reporting any decoding error as `Error::InvalidPreviousMessage`.
+fn parse_previous_message(message_bytes: &[u8]) -> Result<SsbMessage> {
+    from_slice::<SsbMessage>(message_bytes).context(InvalidPreviousMessage {
         message: message_bytes.to_owned(),
+    })
+}
+
+/// Check that the `key` of the previous message is the hash (as computed by `hasher`) of its
+/// `value`, failing with `Error::PreviousKeyMismatch` if it is not.
+fn check_previous_key(
+    message_bytes: &[u8],
+    previous: &SsbMessage,
+    hasher: &dyn MessageHasher,
+) -> Result<()> {
+    match message_value_matching_key_with_hasher(message_bytes, &previous.key, hasher) {
+        Err(Error::ActualHashDidNotMatchKey {
+            actual_hash,
+            expected_hash,
+            ..
+        }) => PreviousKeyMismatch {
+            message: message_bytes.to_owned(),
+            actual_hash,
+            expected_hash,
+        }
+        .fail(),
+        result => result.map(|_| ()),
+    }
+}
+
+/// Parse a message, reporting a leading UTF-8 byte order mark as `Error::LeadingBom` and a `key`
+/// which is not a valid message hash as `Error::InvalidKeyFormat` (rather than as a generic
+/// decoding error).
+fn parse_message(message_bytes: &[u8]) -> Result<SsbMessage> {
+    let message = from_slice::<SsbMessage>(message_bytes).map_err(|source| {
+        // Only look at the bytes once decoding has failed, to keep the common case fast.
+        if message_bytes.starts_with(utils::UTF8_BOM) {
+            return LeadingBom {
+                message: message_bytes.to_owned(),
+            }
+            .build();
+        }
+        match from_slice::<MessageKey>(message_bytes) {
+            Ok(MessageKey { key }) if !utils::is_message_hash(&key) => InvalidKeyFormat {
+                message: message_bytes.to_owned(),
+                key,
+            }
+            .build(),
+            _ => match from_slice::<Value>(message_bytes) {
+                Ok(Value::Object(message)) => message
+                    .get("value")
+                    .and_then(|value| unexpected_field_error(value, message_bytes)),
+                _ => None,
+            }
+            .unwrap_or_else(|| {
+                InvalidMessage {
+                    message: message_bytes.to_owned(),
+                }
+                .into_error(source)
+            }),
+        }
     })?;
 
-    let message_value = message.value;
+    // A blob hash is a valid multihash, but not a valid message key.
+    ensure!(
+        matches!(message.key, Multihash::Message(_)),
+        InvalidKeyFormat {
+            message: message_bytes.to_owned(),
+            key: message.key.to_legacy_string(),
+        }
+    );
 
-    message_value_common_checks(
-        &message_value,
-        previous_value.as_ref(),
-        message_bytes,
-        previous_key.as_ref(),
-        // run checks for previous msg
-        true,
-    )?;
+    Ok(message)
+}
+
+/// Check that the hash of the `value` of a message matches its claimed `key`, returning the `value`
+/// in the generic form in which it was hashed.
+fn message_value_matching_key(message_bytes: &[u8], key: &Multihash) -> Result<Value> {
+    message_value_matching_key_with_hasher(message_bytes, key, &LegacyHasher)
+}
 
+/// Extract the `value` of a message, checking that its hash (as computed by `hasher`) matches
+/// `key`.
+fn message_value_matching_key_with_hasher(
+    message_bytes: &[u8],
+    key: &Multihash,
+    hasher: &dyn MessageHasher,
+) -> Result<Value> {
     let verifiable_msg: Value = from_slice(message_bytes).context(InvalidMessage {
         message: message_bytes.to_owned(),
     })?;
 
     // Get the value from the message as this is what was hashed
     let verifiable_msg_value = match verifiable_msg {
-        Value::Object(ref o) => o.get("value").context(InvalidMessageNoValue)?,
-        _ => panic!(),
+        Value::Object(mut o) => o
+            .remove("value".to_owned())
+            .context(InvalidMessageNoValue)?,
+        _ => return InvalidMessageNoValue.fail(),
     };
 
     // Get the "value" from the message as bytes that we can hash.
     let value_bytes =
-        to_vec(verifiable_msg_value, false).context(InvalidMessageCouldNotSerializeValue)?;
-
-    let message_actual_multihash = utils::multihash_from_bytes(&value_bytes);
+        to_vec(&verifiable_msg_value, false).context(InvalidMessageCouldNotSerializeValue)?;
 
     // The hash of the "value" must match the claimed value stored in the "key"
-    ensure!(
-        message_actual_multihash == message.key,
-        ActualHashDidNotMatchKey {
-            message: message_bytes.to_owned(),
-            actual_hash: message_actual_multihash,
-            expected_hash: message.key,
-        }
-    );
+    utils::verify_key_binding_with_hasher(key, &value_bytes, hasher)?;
 
-    Ok(())
+    Ok(verifiable_msg_value)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::error::Error;
+    #[cfg(feature = "parallel")]
+    use crate::error::ErrorKind;
+    #[cfg(feature = "async")]
+    use crate::message::validate_feed_async;
+    use crate::message::{
+        detect_forks, is_valid_message_hash_chain, sort_and_validate, validate_against_candidates,
+        validate_first_message, validate_message_hash_chain,
+        validate_message_hash_chain_for_author, validate_message_hash_chain_from_checkpoint,
+        validate_message_hash_chain_next_sequence, validate_message_hash_chain_of_feed_rev,
+        validate_message_hash_chain_parsed, validate_message_hash_chain_position,
+        validate_message_hash_chain_with_hasher, validate_message_hash_chain_with_options,
+        validate_multi_author_message_hash_chain, validate_ooo_message_hash_chain, validate_tip,
+        SsbMessage, ValidatedPosition,
+    };
+    #[cfg(feature = "parallel")]
     use crate::message::{
-        par_validate_message_hash_chain_of_feed,
+        par_validate_first_messages, par_validate_message_hash_chain_of_feed,
+        par_validate_message_hash_chain_of_feed_cancellable,
+        par_validate_message_hash_chain_of_feed_digest,
+        par_validate_message_hash_chain_of_feed_in_pool,
+        par_validate_message_hash_chain_of_feed_prefix,
+        par_validate_message_hash_chain_of_feed_returning_tip,
+        par_validate_message_hash_chain_of_feed_stats,
+        par_validate_message_hash_chain_of_feed_summary,
+        par_validate_message_hash_chain_of_feed_with_allowed_authors,
         par_validate_multi_author_message_hash_chain_of_feed,
-        par_validate_ooo_message_hash_chain_of_feed, validate_message_hash_chain,
-        validate_multi_author_message_hash_chain, validate_ooo_message_hash_chain,
+        par_validate_ooo_message_hash_chain_of_feed, validate_feed_bytes, validate_ooo_batch_dense,
     };
+    use crate::options::ValidationOptions;
     use crate::test_data::*;
+    #[cfg(feature = "parallel")]
+    use crate::utils::FeedDigest;
+    use crate::utils::{LegacyHasher, MessageHasher};
+    use ssb_legacy_msg_data::json::from_slice;
+    use ssb_multiformats::multihash::Multihash;
+    #[cfg(feature = "parallel")]
+    use std::collections::HashSet;
+    #[cfg(feature = "parallel")]
+    use std::sync::atomic::AtomicBool;
+
+    #[test]
+    fn it_returns_the_parsed_message() {
+        let message =
+            validate_message_hash_chain_parsed(MESSAGE_2.as_bytes(), Some(MESSAGE_1.as_bytes()))
+                .unwrap();
+        assert_eq!(message.value.sequence, 2);
+        assert_eq!(message.timestamp.map(f64::from), Some(1571140551485.0));
+
+        assert!(
+            validate_message_hash_chain_parsed::<_, &[u8]>(MESSAGE_2.as_bytes(), None).is_err()
+        );
+    }
+
+    #[test]
+    fn it_detects_a_negative_receive_timestamp_when_enabled() {
+        let message = from_slice::<SsbMessage>(MESSAGE_1.as_bytes()).unwrap();
+        assert_eq!(message.timestamp.map(f64::from), Some(1571140551481.0));
+
+        let options = ValidationOptions {
+            validate_kvt_timestamp: true,
+            ..Default::default()
+        };
+        assert!(validate_message_hash_chain_with_options::<_, &[u8]>(
+            MESSAGE_1.as_bytes(),
+            None,
+            &options
+        )
+        .is_ok());
+
+        let negative = MESSAGE_1.replace("\"timestamp\": 1571140551481", "\"timestamp\": -1");
+        assert!(validate_message_hash_chain::<_, &[u8]>(negative.as_bytes(), None).is_ok());
+        match validate_message_hash_chain_with_options::<_, &[u8]>(
+            negative.as_bytes(),
+            None,
+            &options,
+        ) {
+            Err(Error::InvalidReceiveTimestamp { message: _ }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_detects_a_leading_bom() {
+        let mut message = b"\xef\xbb\xbf".to_vec();
+        message.extend_from_slice(MESSAGE_1.as_bytes());
+        match validate_message_hash_chain::<_, &[u8]>(&message, None) {
+            Err(Error::LeadingBom { message: _ }) => {}
+            _ => panic!(),
+        }
+    }
 
     #[test]
     fn it_works_multi_author() {
@@ -450,6 +1859,29 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn it_detects_a_gap_in_a_dense_ooo_batch() {
+        let messages = [MESSAGE_3.as_bytes(), MESSAGE_2.as_bytes()];
+        assert!(validate_ooo_batch_dense(&messages).is_ok());
+
+        let messages = [MESSAGE_3.as_bytes(), MESSAGE_1.as_bytes()];
+        match validate_ooo_batch_dense(&messages) {
+            Err(Error::InvalidSequenceNumber {
+                actual: 3,
+                expected: 2,
+                ..
+            }) => {}
+            _ => panic!(),
+        }
+
+        let messages = [MESSAGE_2.as_bytes(), MESSAGE_2.as_bytes()];
+        match validate_ooo_batch_dense(&messages) {
+            Err(Error::ForkedFeed { previous_seq: 1 }) => {}
+            _ => panic!(),
+        }
+    }
+
     #[test]
     fn it_validates_a_private_message_ooo() {
         let result = validate_ooo_message_hash_chain::<_, &[u8]>(MESSAGE_PRIVATE.as_bytes(), None);
@@ -467,6 +1899,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "parallel")]
     #[test]
     fn par_validate_multi_author_message_hash_chain_of_feed_works() {
         let messages = [
@@ -479,53 +1912,375 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_validate_ooo_message_hash_chain_of_feed_with_first_message_works() {
+        let messages = [
+            MESSAGE_1.as_bytes(),
+            MESSAGE_3.as_bytes(),
+            MESSAGE_2.as_bytes(),
+        ];
+
+        let result = par_validate_ooo_message_hash_chain_of_feed(&messages[..]);
+        assert!(result.is_ok());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_validate_ooo_message_hash_chain_of_feed_without_first_message_works() {
+        let messages = [MESSAGE_3.as_bytes(), MESSAGE_2.as_bytes()];
+
+        let result = par_validate_ooo_message_hash_chain_of_feed(&messages[..]);
+        assert!(result.is_ok());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_validate_ooo_message_hash_chain_of_feed_detects_a_second_author() {
+        let messages = [
+            MESSAGE_3.as_bytes(),
+            MESSAGE_1.as_bytes(),
+            MESSAGE_2_INCORRECT_AUTHOR.as_bytes(),
+        ];
+
+        let result = par_validate_ooo_message_hash_chain_of_feed(&messages[..]);
+        match result {
+            Err(Error::AuthorsDidNotMatch {
+                previous_author,
+                author,
+            }) => {
+                assert_eq!(
+                    previous_author,
+                    "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519"
+                );
+                assert_ne!(author, previous_author);
+            }
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_works_first_message() {
+        assert!(validate_message_hash_chain::<_, &[u8]>(MESSAGE_1.as_bytes(), None).is_ok());
+    }
+
+    #[test]
+    fn it_works_second_message() {
+        assert!(
+            validate_message_hash_chain(MESSAGE_2.as_bytes(), Some(MESSAGE_1.as_bytes())).is_ok()
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_validate_message_hash_chain_of_feed_first_messages_works() {
+        let messages = [MESSAGE_1.as_bytes(), MESSAGE_2.as_bytes()];
+
+        let result = par_validate_message_hash_chain_of_feed::<_, &[u8]>(&messages[..], None);
+        assert!(result.is_ok());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_validate_first_messages_works() {
+        let messages = [MESSAGE_1.as_bytes(), MESSAGE_1.as_bytes()];
+        assert!(par_validate_first_messages(&messages).is_ok());
+
+        let messages = [MESSAGE_1.as_bytes(), MESSAGE_2.as_bytes()];
+        match par_validate_first_messages(&messages) {
+            Err(Error::FirstMessageDidNotHaveSequenceOfOne { message: _ }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn validate_feed_bytes_works() {
+        let messages = vec![MESSAGE_1.as_bytes().to_vec(), MESSAGE_2.as_bytes().to_vec()];
+        assert!(validate_feed_bytes(&messages).is_ok());
+
+        let skipped_first = vec![MESSAGE_2.as_bytes().to_vec()];
+        assert!(validate_feed_bytes(&skipped_first).is_err());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_validate_message_hash_chain_of_feed_with_prev_works() {
+        let messages = [MESSAGE_2.as_bytes(), MESSAGE_3.as_bytes()];
+
+        let result =
+            par_validate_message_hash_chain_of_feed(&messages[..], Some(MESSAGE_1.as_bytes()));
+        assert!(result.is_ok());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_validate_message_hash_chain_of_feed_with_owned_prev_works() {
+        let messages = [MESSAGE_2.as_bytes(), MESSAGE_3.as_bytes()];
+        let previous: Vec<u8> = MESSAGE_1.as_bytes().to_vec();
+
+        let result = par_validate_message_hash_chain_of_feed(&messages[..], Some(previous));
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn validate_message_hash_chain_of_feed_rev_works() {
+        let messages = [
+            MESSAGE_3.as_bytes(),
+            MESSAGE_2.as_bytes(),
+            MESSAGE_1.as_bytes(),
+        ];
+        let result = validate_message_hash_chain_of_feed_rev::<_, &[u8]>(&messages[..], None);
+        assert!(result.is_ok());
+
+        let messages = [MESSAGE_3.as_bytes(), MESSAGE_2.as_bytes()];
+        let result = validate_message_hash_chain_of_feed_rev(&messages[..], Some(MESSAGE_1));
+        assert!(result.is_ok());
+
+        let messages = [MESSAGE_1.as_bytes(), MESSAGE_2.as_bytes()];
+        let result = validate_message_hash_chain_of_feed_rev::<_, &[u8]>(&messages[..], None);
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_validate_message_hash_chain_of_feed_summary_works() {
+        let messages = [
+            MESSAGE_1.as_bytes(),
+            MESSAGE_2.as_bytes(),
+            MESSAGE_3.as_bytes(),
+        ];
+        let summary = par_validate_message_hash_chain_of_feed_summary::<_, &[u8]>(&messages, None);
+        assert_eq!(summary.validated, 3);
+        assert!(summary.first_error.is_none());
+        assert_eq!(
+            summary.author.as_deref(),
+            Some("@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519")
+        );
+        assert_eq!(summary.highest_sequence, 3);
+
+        let messages = [
+            MESSAGE_1.as_bytes(),
+            MESSAGE_2_FORK.as_bytes(),
+            MESSAGE_3.as_bytes(),
+        ];
+        let summary = par_validate_message_hash_chain_of_feed_summary::<_, &[u8]>(&messages, None);
+        assert_eq!(summary.validated, 1);
+        match summary.first_error {
+            Some((1, Error::ForkedFeed { previous_seq: 1 })) => {}
+            _ => panic!(),
+        }
+        assert_eq!(summary.highest_sequence, 1);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_validate_message_hash_chain_of_feed_in_pool_works() {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let messages = [
+            MESSAGE_1.as_bytes(),
+            MESSAGE_2.as_bytes(),
+            MESSAGE_3.as_bytes(),
+        ];
+        assert!(par_validate_message_hash_chain_of_feed_in_pool::<_, &[u8]>(
+            &pool, &messages, None
+        )
+        .is_ok());
+        assert!(par_validate_message_hash_chain_of_feed_in_pool(
+            &pool,
+            &messages[1..],
+            Some(MESSAGE_2.as_bytes())
+        )
+        .is_err());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_validate_message_hash_chain_of_feed_returning_tip_works() {
+        let messages = [
+            MESSAGE_1.as_bytes(),
+            MESSAGE_2.as_bytes(),
+            MESSAGE_3.as_bytes(),
+        ];
+        let tip =
+            par_validate_message_hash_chain_of_feed_returning_tip::<_, &[u8]>(&messages, None);
+        assert_eq!(
+            tip.unwrap(),
+            Some(from_slice::<SsbMessage>(MESSAGE_3.as_bytes()).unwrap().key)
+        );
+
+        let empty: [&[u8]; 0] = [];
+        let tip = par_validate_message_hash_chain_of_feed_returning_tip(&empty, Some(MESSAGE_3));
+        assert_eq!(tip.unwrap(), None);
+
+        let messages = [MESSAGE_1.as_bytes(), MESSAGE_3.as_bytes()];
+        assert!(
+            par_validate_message_hash_chain_of_feed_returning_tip::<_, &[u8]>(&messages, None)
+                .is_err()
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_validate_empty_batches_is_ok() {
+        let empty: [&[u8]; 0] = [];
+        assert!(par_validate_message_hash_chain_of_feed::<_, &[u8]>(&empty, None).is_ok());
+        assert!(par_validate_message_hash_chain_of_feed(&empty, Some(MESSAGE_1)).is_ok());
+        assert!(par_validate_ooo_message_hash_chain_of_feed(&empty).is_ok());
+        assert!(par_validate_multi_author_message_hash_chain_of_feed(&empty).is_ok());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_validate_message_hash_chain_of_feed_stats_works() {
+        let messages = [
+            MESSAGE_1.as_bytes(),
+            MESSAGE_2.as_bytes(),
+            MESSAGE_3.as_bytes(),
+        ];
+        let stats = par_validate_message_hash_chain_of_feed_stats::<_, &[u8]>(&messages, None);
+        assert_eq!(stats.validated, 3);
+        assert!(stats.errors.is_empty());
+
+        // Every message is validated, not only those before the first invalid message.
+        let messages = [
+            MESSAGE_1.as_bytes(),
+            MESSAGE_3.as_bytes(),
+            MESSAGE_3.as_bytes(),
+            MESSAGE_1.as_bytes(),
+        ];
+        let stats = par_validate_message_hash_chain_of_feed_stats::<_, &[u8]>(&messages, None);
+        assert_eq!(stats.validated, 1);
+        assert_eq!(stats.errors.len(), 1);
+        assert_eq!(stats.errors[&ErrorKind::InvalidSequenceNumber], 3);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_validate_message_hash_chain_of_feed_with_allowed_authors_works() {
+        let messages = [MESSAGE_1.as_bytes(), MESSAGE_2.as_bytes()];
+        let author = "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519".to_string();
+
+        let allowed: HashSet<String> = vec![author].into_iter().collect();
+        assert!(
+            par_validate_message_hash_chain_of_feed_with_allowed_authors::<_, &[u8]>(
+                &messages,
+                None,
+                Some(&allowed)
+            )
+            .is_ok()
+        );
+        assert!(
+            par_validate_message_hash_chain_of_feed_with_allowed_authors::<_, &[u8]>(
+                &messages, None, None
+            )
+            .is_ok()
+        );
+
+        let others: HashSet<String> =
+            vec!["@AzjbJaGHHbwSLbP/7LPKIoG2Wk1NRwpoZzX8YPZhGw0=.ed25519".to_string()]
+                .into_iter()
+                .collect();
+        let result = par_validate_message_hash_chain_of_feed_with_allowed_authors::<_, &[u8]>(
+            &messages,
+            None,
+            Some(&others),
+        );
+        match result {
+            Err(Error::AuthorNotAllowed { message: _, author }) => {
+                assert_eq!(
+                    author,
+                    "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519"
+                )
+            }
+            _ => panic!(),
+        }
+    }
+
+    #[cfg(feature = "parallel")]
     #[test]
-    fn par_validate_ooo_message_hash_chain_of_feed_with_first_message_works() {
+    fn par_validate_message_hash_chain_of_feed_digest_works() {
         let messages = [
             MESSAGE_1.as_bytes(),
-            MESSAGE_3.as_bytes(),
             MESSAGE_2.as_bytes(),
+            MESSAGE_3.as_bytes(),
         ];
+        let digest =
+            par_validate_message_hash_chain_of_feed_digest::<_, &[u8]>(&messages, None).unwrap();
 
-        let result = par_validate_ooo_message_hash_chain_of_feed(&messages[..]);
-        assert!(result.is_ok());
-    }
-
-    #[test]
-    fn par_validate_ooo_message_hash_chain_of_feed_without_first_message_works() {
-        let messages = [MESSAGE_3.as_bytes(), MESSAGE_2.as_bytes()];
-
-        let result = par_validate_ooo_message_hash_chain_of_feed(&messages[..]);
-        assert!(result.is_ok());
-    }
+        let mut expected = FeedDigest::new();
+        for msg in messages.iter() {
+            expected.update(&from_slice::<SsbMessage>(msg).unwrap().key);
+        }
+        assert_eq!(digest, expected.finalize());
 
-    #[test]
-    fn it_works_first_message() {
-        assert!(validate_message_hash_chain::<_, &[u8]>(MESSAGE_1.as_bytes(), None).is_ok());
-    }
+        let prefix =
+            par_validate_message_hash_chain_of_feed_digest::<_, &[u8]>(&messages[..2], None)
+                .unwrap();
+        assert_ne!(digest, prefix);
 
-    #[test]
-    fn it_works_second_message() {
+        let invalid = [MESSAGE_1.as_bytes(), MESSAGE_3.as_bytes()];
         assert!(
-            validate_message_hash_chain(MESSAGE_2.as_bytes(), Some(MESSAGE_1.as_bytes())).is_ok()
+            par_validate_message_hash_chain_of_feed_digest::<_, &[u8]>(&invalid, None).is_err()
         );
     }
 
+    #[cfg(feature = "parallel")]
     #[test]
-    fn par_validate_message_hash_chain_of_feed_first_messages_works() {
-        let messages = [MESSAGE_1.as_bytes(), MESSAGE_2.as_bytes()];
+    fn par_validate_message_hash_chain_of_feed_cancellable_works() {
+        let messages = [
+            MESSAGE_1.as_bytes(),
+            MESSAGE_2.as_bytes(),
+            MESSAGE_3.as_bytes(),
+        ];
+        let cancel = AtomicBool::new(false);
+        assert!(
+            par_validate_message_hash_chain_of_feed_cancellable::<_, &[u8]>(
+                &messages, None, &cancel
+            )
+            .is_ok()
+        );
 
-        let result = par_validate_message_hash_chain_of_feed::<_, &[u8]>(&messages[..], None);
-        assert!(result.is_ok());
+        let cancel = AtomicBool::new(true);
+        match par_validate_message_hash_chain_of_feed_cancellable::<_, &[u8]>(
+            &messages, None, &cancel,
+        ) {
+            Err(Error::Cancelled) => {}
+            _ => panic!(),
+        }
     }
 
+    #[cfg(feature = "parallel")]
     #[test]
-    fn par_validate_message_hash_chain_of_feed_with_prev_works() {
-        let messages = [MESSAGE_2.as_bytes(), MESSAGE_3.as_bytes()];
+    fn par_validate_message_hash_chain_of_feed_prefix_works() {
+        let messages = [
+            MESSAGE_1.as_bytes(),
+            MESSAGE_2.as_bytes(),
+            MESSAGE_3.as_bytes(),
+        ];
+        let result = par_validate_message_hash_chain_of_feed_prefix::<_, &[u8]>(&messages, None);
+        assert!(matches!(result, Ok(3)));
 
-        let result =
-            par_validate_message_hash_chain_of_feed(&messages[..], Some(MESSAGE_1.as_bytes()));
-        assert!(result.is_ok());
+        let messages = [
+            MESSAGE_1.as_bytes(),
+            MESSAGE_2.as_bytes(),
+            MESSAGE_2.as_bytes(),
+        ];
+        let result = par_validate_message_hash_chain_of_feed_prefix::<_, &[u8]>(&messages, None);
+        match result {
+            Err((
+                2,
+                Error::InvalidSequenceNumber {
+                    message: _,
+                    actual: 2,
+                    expected: 3,
+                },
+            )) => {}
+            _ => panic!(),
+        }
     }
 
     #[test]
@@ -567,6 +2322,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn it_detects_non_monotonic_timestamp_when_enabled() {
+        let message = MESSAGE_2.replace("1470187292812", "1470186877574");
+        let options = ValidationOptions {
+            enforce_monotonic_timestamps: true,
+            ..Default::default()
+        };
+        // The key no longer matches the value, but the timestamp is checked first.
+        let result = validate_message_hash_chain_with_options(
+            message.as_bytes(),
+            Some(MESSAGE_1.as_bytes()),
+            &options,
+        );
+        match result {
+            Err(Error::NonMonotonicTimestamp { previous, current }) => {
+                assert_eq!(previous, 1470186877575.0);
+                assert_eq!(current, 1470186877574.0);
+            }
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_detects_content_too_deep_when_enabled() {
+        let options = ValidationOptions {
+            max_content_depth: Some(1),
+            ..Default::default()
+        };
+        let result = validate_message_hash_chain_with_options(
+            MESSAGE_2.as_bytes(),
+            Some(MESSAGE_1.as_bytes()),
+            &options,
+        );
+        match result {
+            Err(Error::ContentTooDeep {
+                message: _,
+                max_depth: 1,
+            }) => {}
+            _ => panic!(),
+        }
+
+        let options = ValidationOptions {
+            max_content_depth: Some(2),
+            ..Default::default()
+        };
+        assert!(validate_message_hash_chain_with_options(
+            MESSAGE_2.as_bytes(),
+            Some(MESSAGE_1.as_bytes()),
+            &options
+        )
+        .is_ok());
+    }
+
     #[test]
     fn it_detects_incorrect_author() {
         let result = validate_message_hash_chain(
@@ -600,6 +2408,9 @@ mod tests {
             MESSAGE_2_INCORRECT_KEY.as_bytes(),
             Some(MESSAGE_1.as_bytes()),
         );
+        let display = result.as_ref().unwrap_err().to_string();
+        assert!(display.contains("(%kLWDux4wCG+OdQWAHnpBGzGlCehqMLfgLbzlKCvgesU=.sha256)"));
+        assert!(display.contains("(%KLWDux4wCG+OdQWAHnpBGzGlCehqMLfgLbzlKCvgesU=.sha256)"));
         match result {
             Err(Error::ActualHashDidNotMatchKey {
                 message: _,
@@ -610,6 +2421,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn it_detects_an_invalid_key_format() {
+        let message = MESSAGE_2.replace(
+            "%kLWDux4wCG+OdQWAHnpBGzGlCehqMLfgLbzlKCvgesU=.sha256",
+            "%kLWDux4wCG+OdQWAHnpBGzGlCehqMLfgLbzlKCvgesU.sha256",
+        );
+        match validate_message_hash_chain(message.as_bytes(), Some(MESSAGE_1.as_bytes())) {
+            Err(Error::InvalidKeyFormat { message: _, key }) => {
+                assert_eq!(key, "%kLWDux4wCG+OdQWAHnpBGzGlCehqMLfgLbzlKCvgesU.sha256")
+            }
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_detects_a_blob_hash_key() {
+        let message = MESSAGE_2.replace(
+            "%kLWDux4wCG+OdQWAHnpBGzGlCehqMLfgLbzlKCvgesU=.sha256",
+            "&kLWDux4wCG+OdQWAHnpBGzGlCehqMLfgLbzlKCvgesU=.sha256",
+        );
+        let results = vec![
+            validate_message_hash_chain(message.as_bytes(), Some(MESSAGE_1.as_bytes())),
+            validate_ooo_message_hash_chain(message.as_bytes(), Some(MESSAGE_1.as_bytes())),
+            validate_multi_author_message_hash_chain(message.as_bytes()),
+        ];
+        for result in results {
+            match result {
+                Err(Error::InvalidKeyFormat { message: _, key }) => {
+                    assert_eq!(key, "&kLWDux4wCG+OdQWAHnpBGzGlCehqMLfgLbzlKCvgesU=.sha256")
+                }
+                _ => panic!(),
+            }
+        }
+    }
+
     #[test]
     fn it_detects_incorrect_key_for_multi_author() {
         let result = validate_multi_author_message_hash_chain(MESSAGE_2_INCORRECT_KEY.as_bytes());
@@ -623,16 +2469,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn it_detects_a_message_from_an_unexpected_author() {
+        let author = "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519";
+        assert!(
+            validate_message_hash_chain_for_author::<_, &[u8]>(MESSAGE_1, None, author).is_ok()
+        );
+
+        let other_author = "@xzSRT0HSAqGuqu5HxJvqxtp2FJGpt5nRPIHMznLoBao=.ed25519";
+        match validate_message_hash_chain_for_author::<_, &[u8]>(MESSAGE_1, None, other_author) {
+            Err(Error::AuthorsDidNotMatch {
+                previous_author,
+                author: actual,
+            }) => {
+                assert_eq!(previous_author, other_author);
+                assert_eq!(actual, author);
+            }
+            _ => panic!(),
+        }
+    }
+
     #[test]
     fn it_detects_extra_unwanted_field() {
         let result =
             validate_message_hash_chain::<_, &[u8]>(MESSAGE_WITH_EXTRA_FIELD.as_bytes(), None);
-        // code: Message("unknown field `extra`, expected one of ...
         match result {
-            Err(Error::InvalidMessage {
-                source: _,
-                message: _,
-            }) => {}
+            Err(Error::UnexpectedMessageValueField { message: _, field }) => {
+                assert_eq!(field, "extra")
+            }
             _ => panic!(),
         }
     }
@@ -647,6 +2511,317 @@ mod tests {
         }
     }
 
+    #[test]
+    fn it_finds_the_matching_candidate_previous_message() {
+        let candidates = [MESSAGE_2_SIBLING.as_bytes(), MESSAGE_2.as_bytes()];
+        let result = validate_against_candidates(MESSAGE_3, &candidates);
+        assert_eq!(result.unwrap(), 1);
+
+        let candidates = [MESSAGE_2_SIBLING.as_bytes()];
+        match validate_against_candidates(MESSAGE_3, &candidates) {
+            Err(Error::ForkedFeed { previous_seq: 2 }) => {}
+            _ => panic!(),
+        }
+
+        match validate_against_candidates(MESSAGE_2_INCORRECT_KEY, &candidates) {
+            Err(Error::ActualHashDidNotMatchKey { .. }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn it_validates_a_feed_from_async_code() {
+        let messages = vec![MESSAGE_1.as_bytes().to_vec(), MESSAGE_2.as_bytes().to_vec()];
+        assert!(validate_feed_async(messages).await.is_ok());
+
+        let messages = vec![MESSAGE_2.as_bytes().to_vec()];
+        assert!(validate_feed_async(messages).await.is_err());
+    }
+
+    #[test]
+    fn it_detects_a_self_referential_previous() {
+        // A message whose `previous` is its own hash cannot be found for SHA-256, so use a hasher
+        // under which the hash of every message is the key of MESSAGE_1.
+        struct FirstKeyHasher;
+        impl MessageHasher for FirstKeyHasher {
+            fn hash(&self, _value_bytes: &[u8]) -> Multihash {
+                from_slice::<SsbMessage>(MESSAGE_1.as_bytes()).unwrap().key
+            }
+        }
+        let message = MESSAGE_2.replace(
+            "\"key\": \"%kLWDux4wCG+OdQWAHnpBGzGlCehqMLfgLbzlKCvgesU=.sha256\"",
+            "\"key\": \"%/v5mCnV/kmnVtnF3zXtD4tbzoEQo4kRq/0d/bgxP1WI=.sha256\"",
+        );
+        let result =
+            validate_message_hash_chain_with_hasher(&message, Some(MESSAGE_1), &FirstKeyHasher);
+        match result {
+            Err(Error::SelfReferentialPrevious { .. }) => {}
+            _ => panic!(),
+        }
+
+        // The stated key of the message is not its hash, which is reported as such.
+        match validate_message_hash_chain(&message, Some(MESSAGE_1)) {
+            Err(Error::ActualHashDidNotMatchKey { .. }) => {}
+            _ => panic!(),
+        }
+        match validate_ooo_message_hash_chain(&message, Some(MESSAGE_1)) {
+            Err(Error::ActualHashDidNotMatchKey { .. }) => {}
+            _ => panic!(),
+        }
+        match validate_multi_author_message_hash_chain(&message) {
+            Err(Error::ActualHashDidNotMatchKey { .. }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_returns_the_next_sequence() {
+        let next_sequence = validate_message_hash_chain_next_sequence::<_, &[u8]>(MESSAGE_1, None);
+        assert_eq!(next_sequence.unwrap(), 2);
+        let next_sequence = validate_message_hash_chain_next_sequence(MESSAGE_3, Some(MESSAGE_2));
+        assert_eq!(next_sequence.unwrap(), 4);
+        assert!(validate_message_hash_chain_next_sequence::<_, &[u8]>(MESSAGE_2, None).is_err());
+    }
+
+    #[test]
+    fn it_validates_the_first_message() {
+        let key = validate_first_message(MESSAGE_1).unwrap();
+        assert_eq!(
+            key,
+            from_slice::<SsbMessage>(MESSAGE_1.as_bytes()).unwrap().key
+        );
+
+        match validate_first_message(MESSAGE_2) {
+            Err(Error::FirstMessageDidNotHaveSequenceOfOne { .. }) => {}
+            _ => panic!(),
+        }
+        match validate_first_message(MESSAGE_1_INVALID_PREVIOUS) {
+            Err(Error::FirstMessageDidNotHavePreviousOfNull { .. }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn is_valid_message_hash_chain_is_total() {
+        assert!(is_valid_message_hash_chain::<_, &[u8]>(MESSAGE_1, None));
+        assert!(is_valid_message_hash_chain(MESSAGE_2, Some(MESSAGE_1)));
+        assert!(!is_valid_message_hash_chain(MESSAGE_3, Some(MESSAGE_1)));
+
+        for bytes in [
+            &b""[..],
+            b"\xff\xfe",
+            b"null",
+            b"[]",
+            b"{\"value\": 1}",
+            b"{\"key\": \"%\", \"value\": {}}",
+        ] {
+            assert!(!is_valid_message_hash_chain::<_, &[u8]>(bytes, None));
+            assert!(!is_valid_message_hash_chain(MESSAGE_2, Some(bytes)));
+        }
+    }
+
+    #[test]
+    fn it_detects_a_previous_message_whose_key_is_not_its_hash() {
+        // The stated key of the tampered previous message is still the `previous` of MESSAGE_3.
+        let tampered = MESSAGE_2.replace("642763", "642764");
+        match validate_message_hash_chain(MESSAGE_3, Some(&tampered)) {
+            Err(Error::PreviousKeyMismatch { expected_hash, .. }) => assert_eq!(
+                expected_hash,
+                from_slice::<SsbMessage>(MESSAGE_2.as_bytes()).unwrap().key
+            ),
+            _ => panic!(),
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn it_checks_the_previous_key_of_a_batch_once() {
+        let tampered = MESSAGE_2.replace("642763", "642764");
+
+        // The supplied previous message is checked before the first message of the batch.
+        let result = par_validate_message_hash_chain_of_feed(&[MESSAGE_3], Some(&tampered));
+        match result {
+            Err(Error::PreviousKeyMismatch { .. }) => {}
+            _ => panic!(),
+        }
+        // A message of the batch is checked as an element of the batch, not as a previous.
+        let stats = par_validate_message_hash_chain_of_feed_stats(
+            &[tampered.as_str(), MESSAGE_3],
+            Some(MESSAGE_1),
+        );
+        assert_eq!(
+            stats.errors.get(&ErrorKind::ActualHashDidNotMatchKey),
+            Some(&1)
+        );
+        assert_eq!(stats.errors.get(&ErrorKind::PreviousKeyMismatch), None);
+    }
+
+    #[test]
+    fn it_sorts_and_validates_an_unordered_feed() {
+        let messages = [MESSAGE_2, MESSAGE_3, MESSAGE_1];
+        assert_eq!(sort_and_validate(&messages).unwrap(), vec![2, 0, 1]);
+        assert!(sort_and_validate::<&[u8]>(&[]).unwrap().is_empty());
+
+        match sort_and_validate(&[MESSAGE_3, MESSAGE_1]) {
+            Err(Error::BrokenChain { unchained: 1 }) => {}
+            _ => panic!(),
+        }
+        match sort_and_validate(&[MESSAGE_3, MESSAGE_2]) {
+            Err(Error::BrokenChain { unchained: 2 }) => {}
+            _ => panic!(),
+        }
+        match sort_and_validate(&[MESSAGE_1, MESSAGE_2, MESSAGE_2_SIBLING]) {
+            Err(Error::ForkedFeed { previous_seq: 1 }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_validates_the_tip_of_a_feed() {
+        let key = validate_tip(MESSAGE_3, 3).unwrap();
+        assert_eq!(
+            key,
+            from_slice::<SsbMessage>(MESSAGE_3.as_bytes()).unwrap().key
+        );
+
+        match validate_tip(MESSAGE_3, 5000) {
+            Err(Error::InvalidSequenceNumber {
+                actual: 3,
+                expected: 5000,
+                ..
+            }) => {}
+            _ => panic!(),
+        }
+        // The message must be valid in isolation.
+        match validate_tip(MESSAGE_2_FORK, 2) {
+            Err(Error::ActualHashDidNotMatchKey { .. }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_validates_with_a_custom_hasher() {
+        struct ZeroHasher;
+        impl MessageHasher for ZeroHasher {
+            fn hash(&self, _value_bytes: &[u8]) -> Multihash {
+                Multihash::Message([0; 32])
+            }
+        }
+
+        let result =
+            validate_message_hash_chain_with_hasher(MESSAGE_2, Some(MESSAGE_1), &LegacyHasher);
+        assert!(result.is_ok());
+
+        let result =
+            validate_message_hash_chain_with_hasher::<_, &[u8]>(MESSAGE_1, None, &ZeroHasher);
+        match result {
+            Err(Error::ActualHashDidNotMatchKey { actual_hash, .. }) => {
+                assert_eq!(actual_hash, Multihash::Message([0; 32]))
+            }
+            _ => panic!(),
+        }
+        // The key of the previous message is checked with the same hasher.
+        let result =
+            validate_message_hash_chain_with_hasher(MESSAGE_2, Some(MESSAGE_1), &ZeroHasher);
+        match result {
+            Err(Error::PreviousKeyMismatch { actual_hash, .. }) => {
+                assert_eq!(actual_hash, Multihash::Message([0; 32]))
+            }
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_returns_the_position_of_a_message() {
+        let first: SsbMessage = from_slice(MESSAGE_1.as_bytes()).unwrap();
+        let second: SsbMessage = from_slice(MESSAGE_2.as_bytes()).unwrap();
+
+        let position = validate_message_hash_chain_position::<_, &[u8]>(MESSAGE_1, None).unwrap();
+        assert_eq!(position, ValidatedPosition::First { key: first.key });
+
+        let position = validate_message_hash_chain_position(MESSAGE_2, Some(MESSAGE_1)).unwrap();
+        assert_eq!(
+            position,
+            ValidatedPosition::Continuation {
+                key: second.key,
+                sequence: 2
+            }
+        );
+    }
+
+    #[test]
+    fn it_detects_forks_across_a_batch() {
+        let messages = [
+            MESSAGE_2.as_bytes(),
+            MESSAGE_1.as_bytes(),
+            MESSAGE_3.as_bytes(),
+            MESSAGE_2.as_bytes(),
+        ];
+        assert!(detect_forks(&messages[..]).unwrap().is_empty());
+
+        let messages = [
+            MESSAGE_2.as_bytes(),
+            MESSAGE_3.as_bytes(),
+            MESSAGE_1.as_bytes(),
+            MESSAGE_2_SIBLING.as_bytes(),
+        ];
+        let forks = detect_forks(&messages[..]).unwrap();
+        let message_1: SsbMessage = from_slice(MESSAGE_1.as_bytes()).unwrap();
+        let message_2: SsbMessage = from_slice(MESSAGE_2.as_bytes()).unwrap();
+        let sibling: SsbMessage = from_slice(MESSAGE_2_SIBLING.as_bytes()).unwrap();
+        assert_eq!(
+            forks,
+            vec![(message_1.key, vec![message_2.key, sibling.key])]
+        );
+    }
+
+    #[test]
+    fn it_works_from_checkpoint() {
+        let previous: SsbMessage = from_slice(MESSAGE_1.as_bytes()).unwrap();
+        let result = validate_message_hash_chain_from_checkpoint(
+            MESSAGE_2.as_bytes(),
+            &previous.key,
+            previous.value.sequence,
+            &previous.value.author,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn it_detects_fork_from_checkpoint() {
+        let previous: SsbMessage = from_slice(MESSAGE_1.as_bytes()).unwrap();
+        let result = validate_message_hash_chain_from_checkpoint(
+            MESSAGE_2_FORK.as_bytes(),
+            &previous.key,
+            previous.value.sequence,
+            &previous.value.author,
+        );
+        match result {
+            Err(Error::ForkedFeed { previous_seq: 1 }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_detects_incorrect_seq_from_checkpoint() {
+        let previous: SsbMessage = from_slice(MESSAGE_1.as_bytes()).unwrap();
+        let result = validate_message_hash_chain_from_checkpoint(
+            MESSAGE_2.as_bytes(),
+            &previous.key,
+            5,
+            &previous.value.author,
+        );
+        match result {
+            Err(Error::InvalidSequenceNumber {
+                message: _,
+                actual: 2,
+                expected: 6,
+            }) => {}
+            _ => panic!(),
+        }
+    }
+
     #[test]
     fn it_detects_missing_hash_function() {
         let result =
@@ -689,7 +2864,23 @@ mod tests {
             Some(MESSAGE_1.as_bytes()),
         );
         match result {
-            Err(Error::InvalidMessageValueOrder { message: _ }) => {}
+            Err(Error::InvalidMessageValueOrder {
+                message: _,
+                found_order,
+            }) => {
+                assert_eq!(
+                    found_order,
+                    vec![
+                        "previous",
+                        "author",
+                        "sequence",
+                        "hash",
+                        "timestamp",
+                        "content",
+                        "signature"
+                    ]
+                );
+            }
             _ => panic!(),
         }
     }
@@ -1,26 +1,53 @@
 //! Functions for validating message values (ie. just the `value` without `key` and `timestamp`).
-use rayon::prelude::*;
+use std::convert::TryFrom;
+
+#[cfg(feature = "parallel")]
+use rayon::{prelude::*, ThreadPool};
 use serde::{Deserialize, Serialize};
-use snafu::{ensure, OptionExt, ResultExt};
+use snafu::{ensure, IntoError, OptionExt, ResultExt};
 use ssb_legacy_msg_data::{
-    json::from_slice,
-    value::{ContentValue, Value},
+    json::{from_slice, to_string, to_vec},
+    value::{ContentValue, RidiculousStringMap, Value},
     LegacyF64,
 };
-use ssb_multiformats::multihash::Multihash;
+use ssb_multiformats::{multifeed::Multifeed, multihash::Multihash};
 
 use crate::error::{
-    AuthorsDidNotMatch, FirstMessageDidNotHavePreviousOfNull, FirstMessageDidNotHaveSequenceOfOne,
-    ForkedFeed, InvalidBase64, InvalidHashFunction, InvalidMessage, InvalidMessageValueLength,
-    InvalidMessageValueOrder, InvalidPreviousMessage, InvalidSequenceNumber, PreviousWasNull,
-    Result,
+    AuthorsDidNotMatch, CiphertextTooShort, ContentTooDeep, Error,
+    FirstMessageDidNotHavePreviousOfNull, FirstMessageDidNotHaveSequenceOfOne, ForkedFeed,
+    InconsistentFirstMessageMarkers, InvalidAuthorFormat, InvalidBase64, InvalidContent,
+    InvalidContentType, InvalidHashFunction, InvalidLink, InvalidMessage,
+    InvalidMessageCouldNotSerializeValue, InvalidMessageValueLength, InvalidMessageValueOrder,
+    InvalidPreviousMessage, InvalidPreviousReference, InvalidSequenceNumber,
+    InvalidSignatureFormat, InvalidTimestamp, LeadingBom, NonMonotonicTimestamp, PreviousWasNull,
+    Result, SequenceOverflow, SequenceWasZero, UnexpectedMessageValueField,
 };
-use crate::utils;
+use crate::options::ValidationOptions;
+use crate::utils::{self, EncScheme, LegacyHasher, MessageHasher};
+
+/// Length in bytes of the smallest possible private-box: a 24 byte nonce, a 32 byte ephemeral
+/// public key, one 49 byte recipient header and the 16 byte authentication tag of an empty body.
+const MIN_PRIVATE_BOX_LENGTH: usize = 24 + 32 + 49 + 16;
+
+/// The fields of a message value.
+const MESSAGE_VALUE_FIELDS: [&str; 7] = [
+    "previous",
+    "author",
+    "sequence",
+    "timestamp",
+    "hash",
+    "content",
+    "signature",
+];
 
 /// Data type representing the `value` of a message object (`KVT`). More information concerning the
 /// data model can be found
 /// in the [`Metadata` documentation](https://spec.scuttlebutt.nz/feed/messages.html#metadata).
-#[derive(Serialize, Deserialize, Debug)]
+///
+/// There is no borrowing counterpart of this type: the legacy JSON decoder unescapes every string
+/// into an owned buffer (it never hands out `&str` slices of the input), so a `#[serde(borrow)]`
+/// `author` or `signature` could not be deserialized, and a `Cow` would always be owned.
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct SsbMessageValue {
     pub previous: Option<Multihash>,
@@ -32,6 +59,43 @@ pub struct SsbMessageValue {
     pub signature: String,
 }
 
+impl SsbMessageValue {
+    /// Parse the `author` of the message into a typed feed id.
+    ///
+    /// Returns `Error::InvalidAuthorFormat` if the `author` is not a valid feed id (for example
+    /// `@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519`), including when it is followed by
+    /// trailing characters.
+    pub fn feed_id(&self) -> Result<Multifeed> {
+        match Multifeed::from_legacy(self.author.as_bytes()) {
+            Ok((feed_id, [])) => Ok(feed_id),
+            _ => InvalidAuthorFormat {
+                author: self.author.clone(),
+            }
+            .fail(),
+        }
+    }
+}
+
+/// Parse and validate a message value in isolation (see [`validate_message_value_parsed`]). No
+/// checks involving the previous message are performed.
+impl TryFrom<&[u8]> for SsbMessageValue {
+    type Error = Error;
+
+    fn try_from(message_bytes: &[u8]) -> Result<Self> {
+        validate_message_value_parsed(message_bytes)
+    }
+}
+
+/// Parse and validate a message value in isolation (see [`validate_message_value_parsed`]). No
+/// checks involving the previous message are performed.
+impl TryFrom<&str> for SsbMessageValue {
+    type Error = Error;
+
+    fn try_from(message: &str) -> Result<Self> {
+        validate_message_value_parsed(message)
+    }
+}
+
 /// Batch validate a collection of message values, all by the same author, ordered by ascending sequence
 /// number, with no missing messages.
 ///
@@ -47,6 +111,9 @@ pub struct SsbMessageValue {
 /// This will mainly be useful during replication. Collect all the latest messages from a feed you're
 /// replicating and batch validate all the messages at once.
 ///
+/// An empty collection of messages is valid (whatever the `previous`); callers for which an empty
+/// batch is an error must check for it before validating.
+///
 /// # Example
 ///```
 ///use ssb_validate::message_value::par_validate_message_value_hash_chain_of_feed;
@@ -88,14 +155,57 @@ pub struct SsbMessageValue {
 /// let result = par_validate_message_value_hash_chain_of_feed::<_, &[u8]>(&messages, None);
 /// assert!(result.is_ok());
 ///```
-pub fn par_validate_message_value_hash_chain_of_feed<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+#[cfg(feature = "parallel")]
+pub fn par_validate_message_value_hash_chain_of_feed<T, U>(
+    messages: &[T],
+    previous: Option<U>,
+) -> Result<()>
+where
+    [T]: ParallelSlice<T>,
+    T: AsRef<[u8]> + Sync,
+    U: AsRef<[u8]> + Sync + Send,
+{
+    par_validate_message_value_hash_chain_of_feed_with_options(
+        messages,
+        previous,
+        &ValidationOptions::default(),
+    )
+}
+
+/// Batch validate a collection of message values, all by the same author, ordered by ascending
+/// sequence number, with no missing messages, using the given thread pool rather than the global
+/// pool.
+///
+/// This performs the same checks as [`par_validate_message_value_hash_chain_of_feed`]. Running
+/// the validation on a dedicated pool bounds the number of threads it occupies.
+#[cfg(feature = "parallel")]
+pub fn par_validate_message_value_hash_chain_of_feed_in_pool<T, U>(
+    pool: &ThreadPool,
+    messages: &[T],
+    previous: Option<U>,
+) -> Result<()>
+where
+    [T]: ParallelSlice<T>,
+    T: AsRef<[u8]> + Sync,
+    U: AsRef<[u8]> + Sync + Send,
+{
+    pool.install(|| par_validate_message_value_hash_chain_of_feed(messages, previous))
+}
+
+/// Batch validate a collection of message values, all by the same author, ordered by ascending
+/// sequence number, with no missing messages, performing the optional checks enabled in `options`.
+///
+/// See [`par_validate_message_value_hash_chain_of_feed`].
+#[cfg(feature = "parallel")]
+pub fn par_validate_message_value_hash_chain_of_feed_with_options<T, U>(
     messages: &[T],
     previous: Option<U>,
+    options: &ValidationOptions,
 ) -> Result<()>
 where
     [T]: ParallelSlice<T>,
-    T: Sync,
-    U: Sync + Send + Copy,
+    T: AsRef<[u8]> + Sync,
+    U: AsRef<[u8]> + Sync + Send,
 {
     messages
         .par_iter()
@@ -104,12 +214,13 @@ where
             || (),
             |_, (idx, msg)| {
                 if idx == 0 {
-                    let prev = previous.map(|prev| prev.as_ref().to_owned());
-                    validate_message_value_hash_chain(msg.as_ref(), prev)
+                    let prev = previous.as_ref().map(|prev| prev.as_ref());
+                    validate_message_value_hash_chain_with_options(msg.as_ref(), prev, options)
                 } else {
-                    validate_message_value_hash_chain(
+                    validate_message_value_hash_chain_with_options(
                         msg.as_ref(),
                         Some(messages[idx - 1].as_ref()),
+                        options,
                     )
                 }
             },
@@ -183,6 +294,172 @@ where
 pub fn validate_message_value_hash_chain<T: AsRef<[u8]>, U: AsRef<[u8]>>(
     message_bytes: T,
     previous_msg_bytes: Option<U>,
+) -> Result<()> {
+    validate_message_value_hash_chain_with_options(
+        message_bytes,
+        previous_msg_bytes,
+        &ValidationOptions::default(),
+    )
+}
+
+/// Validate a message value, already decoded as a `serde_json::Value`, in relation to the previous
+/// message value.
+///
+/// This performs exactly the same checks as [`validate_message_value_hash_chain`]. The values are
+/// serialized once, in the form in which they are hashed and signed, for the checks which need the
+/// raw bytes (such as the field order and length checks). Since the order of the fields matters,
+/// the `serde_json` dependency is built with its `preserve_order` feature.
+///
+/// Requires the `serde_json` feature.
+///
+/// # Example
+///```
+///use ssb_validate::message_value::validate_message_value_from_json;
+///use ssb_validate::test_data::{MESSAGE_VALUE_1, MESSAGE_VALUE_2};
+///let value_1: serde_json::Value = serde_json::from_str(MESSAGE_VALUE_1).unwrap();
+///let value_2: serde_json::Value = serde_json::from_str(MESSAGE_VALUE_2).unwrap();
+///assert!(validate_message_value_from_json(&value_2, Some(&value_1)).is_ok());
+///```
+#[cfg(feature = "serde_json")]
+pub fn validate_message_value_from_json(
+    value: &serde_json::Value,
+    previous: Option<&serde_json::Value>,
+) -> Result<()> {
+    let message_bytes = legacy_bytes_from_json(value)?;
+    let previous_msg_bytes = previous.map(legacy_bytes_from_json).transpose()?;
+
+    validate_message_value_hash_chain(message_bytes, previous_msg_bytes)
+}
+
+/// Serialize a `serde_json::Value` in the form in which message values are hashed and signed.
+#[cfg(feature = "serde_json")]
+fn legacy_bytes_from_json(value: &serde_json::Value) -> Result<Vec<u8>> {
+    // Serializing a `serde_json::Value` cannot fail (all of its map keys are strings).
+    let json_bytes = serde_json::to_vec(value).unwrap_or_default();
+    let legacy_value = from_slice::<Value>(&json_bytes).context(InvalidMessage {
+        message: json_bytes.clone(),
+    })?;
+
+    to_vec(&legacy_value, false).context(InvalidMessageCouldNotSerializeValue)
+}
+
+/// The previous message of a feed, against which the continuity of a message value is checked.
+#[derive(Debug, Clone)]
+pub enum Previous<T> {
+    /// The JSON encoded previous message value.
+    Full(T),
+    /// The salient facts of the previous message value, as indexed by a database. This avoids
+    /// parsing and hashing the previous message value.
+    Summary {
+        /// The key (hash) of the previous message.
+        key: Multihash,
+        /// The sequence number of the previous message.
+        sequence: u64,
+        /// The author of the previous message.
+        author: String,
+    },
+}
+
+/// Validate a message value in relation to the previous message, which may be supplied either in
+/// full or as a summary of its key, sequence and author.
+///
+/// This performs the same checks as [`validate_message_value_hash_chain`]. When a
+/// [`Previous::Summary`] is given, the continuity of the feed is checked against it directly:
+/// - the `previous` of the message matches the summary `key`
+/// - the `sequence` of the message is one greater than the summary `sequence`
+/// - the `author` of the message matches the summary `author`
+///
+/// `previous` will be `None` only when `message_bytes` is the first message by that author.
+///
+/// # Example
+///```
+///use ssb_multiformats::multihash::Multihash;
+///use ssb_validate::message_value::{validate_message_value_hash_chain_with_previous, Previous};
+///use ssb_validate::test_data::MESSAGE_VALUE_2;
+///
+///let (key, _) =
+///    Multihash::from_legacy(b"%/v5mCnV/kmnVtnF3zXtD4tbzoEQo4kRq/0d/bgxP1WI=.sha256").unwrap();
+///let previous: Previous<&[u8]> = Previous::Summary {
+///    key,
+///    sequence: 1,
+///    author: "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519".to_string(),
+///};
+///let result = validate_message_value_hash_chain_with_previous(MESSAGE_VALUE_2, Some(previous));
+///assert!(result.is_ok());
+///```
+pub fn validate_message_value_hash_chain_with_previous<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+    message_bytes: T,
+    previous: Option<Previous<U>>,
+) -> Result<()> {
+    match previous {
+        None => validate_message_value_hash_chain::<_, &[u8]>(message_bytes, None),
+        Some(Previous::Full(previous_bytes)) => {
+            validate_message_value_hash_chain(message_bytes, Some(previous_bytes))
+        }
+        Some(Previous::Summary {
+            key,
+            sequence,
+            author,
+        }) => {
+            let message_bytes = message_bytes.as_ref();
+            let message_value = parse_message_value(message_bytes)?;
+
+            // perform common validation checks without `previous` message
+            message_value_common_checks(
+                &message_value,
+                None,
+                message_bytes,
+                None,
+                false,
+                &ValidationOptions::default(),
+            )?;
+
+            check_continuity_with_summary(&message_value, message_bytes, &key, sequence, &author)
+        }
+    }
+}
+
+/// Validate a message value in relation to the previous message value, performing the optional
+/// checks enabled in `options`.
+///
+/// See [`validate_message_value_hash_chain`].
+pub fn validate_message_value_hash_chain_with_options<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+    message_bytes: T,
+    previous_msg_bytes: Option<U>,
+    options: &ValidationOptions,
+) -> Result<()> {
+    validate_message_value_hash_chain_with_options_and_hasher(
+        message_bytes,
+        previous_msg_bytes,
+        options,
+        &LegacyHasher,
+    )
+}
+
+/// Validate a message value in relation to the previous message value, computing the key of the
+/// previous message value with `hasher` rather than with the hashing convention of the classic
+/// network.
+///
+/// This performs exactly the same checks as [`validate_message_value_hash_chain`]; passing
+/// [`LegacyHasher`] is equivalent to calling it directly.
+pub fn validate_message_value_hash_chain_with_hasher<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+    message_bytes: T,
+    previous_msg_bytes: Option<U>,
+    hasher: &dyn MessageHasher,
+) -> Result<()> {
+    validate_message_value_hash_chain_with_options_and_hasher(
+        message_bytes,
+        previous_msg_bytes,
+        &ValidationOptions::default(),
+        hasher,
+    )
+}
+
+fn validate_message_value_hash_chain_with_options_and_hasher<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+    message_bytes: T,
+    previous_msg_bytes: Option<U>,
+    options: &ValidationOptions,
+    hasher: &dyn MessageHasher,
 ) -> Result<()> {
     let message_bytes = message_bytes.as_ref();
     // msg seq is 1 larger than previous
@@ -193,26 +470,88 @@ pub fn validate_message_value_hash_chain<T: AsRef<[u8]>, U: AsRef<[u8]>>(
                     message: message.as_ref().to_owned(),
                 },
             )?;
-            let previous_key = utils::multihash_from_bytes(message.as_ref());
+            let previous_key = hasher.hash(message.as_ref());
             (Some(previous), Some(previous_key))
         }
         None => (None, None),
     };
 
-    let message_value = from_slice::<SsbMessageValue>(message_bytes).context(InvalidMessage {
-        message: message_bytes.to_owned(),
-    })?;
+    validate_message_value_against_previous(
+        message_bytes,
+        previous_value.as_ref().zip(previous_key.as_ref()),
+        options,
+    )?;
+
+    Ok(())
+}
+
+/// Parse a message value, reporting a leading UTF-8 byte order mark as `Error::LeadingBom` (rather
+/// than as a generic decoding error).
+fn parse_message_value(message_bytes: &[u8]) -> Result<SsbMessageValue> {
+    from_slice::<SsbMessageValue>(message_bytes).map_err(|source| {
+        if message_bytes.starts_with(utils::UTF8_BOM) {
+            LeadingBom {
+                message: message_bytes.to_owned(),
+            }
+            .build()
+        } else {
+            match from_slice::<Value>(message_bytes) {
+                Ok(value) => unexpected_field_error(&value, message_bytes),
+                Err(_) => None,
+            }
+            .unwrap_or_else(|| {
+                InvalidMessage {
+                    message: message_bytes.to_owned(),
+                }
+                .into_error(source)
+            })
+        }
+    })
+}
+
+/// If the given message value is an object with a field which is not one of the fields of a
+/// message value (such as a wrapper added by another tool), the
+/// `Error::UnexpectedMessageValueField` for the first such field.
+///
+/// This is only used once decoding has failed, to give a clearer error than the decoder's.
+pub(crate) fn unexpected_field_error(message_value: &Value, message_bytes: &[u8]) -> Option<Error> {
+    match message_value {
+        Value::Object(object) => object
+            .iter()
+            .find(|(field, _)| !MESSAGE_VALUE_FIELDS.contains(&field.as_str()))
+            .map(|(field, _)| {
+                UnexpectedMessageValueField {
+                    message: message_bytes.to_owned(),
+                    field: field.to_owned(),
+                }
+                .build()
+            }),
+        _ => None,
+    }
+}
+
+/// Validate a message value in relation to an already-parsed previous message value and its key,
+/// returning the parsed message value.
+///
+/// `previous` will be `None` only when `message_bytes` is the first message by that author.
+pub(crate) fn validate_message_value_against_previous(
+    message_bytes: &[u8],
+    previous: Option<(&SsbMessageValue, &Multihash)>,
+    options: &ValidationOptions,
+) -> Result<SsbMessageValue> {
+    let message_value = parse_message_value(message_bytes)?;
 
     message_value_common_checks(
         &message_value,
-        previous_value.as_ref(),
+        previous.map(|(previous_value, _)| previous_value),
         message_bytes,
-        previous_key.as_ref(),
+        previous.map(|(_, previous_key)| previous_key),
         // run checks for previous msg
         true,
+        options,
     )?;
 
-    Ok(())
+    Ok(message_value)
 }
 
 /// Validate a single message value (in isolation).
@@ -230,7 +569,8 @@ pub fn validate_message_value_hash_chain<T: AsRef<[u8]>, U: AsRef<[u8]>>(
 ///
 /// - The fields (keys) of the message value are in the correct order
 /// - The hash signature is `sha256`
-/// - The message `content` is canonical base64 (if `content` is a string)
+/// - The timestamp is not negative
+/// - The message `content` is canonical base64 with a `.box` or `.box2` suffix (if `content` is a string)
 /// - The message value does not exceed 8192 UTF-16 code units when serialized as a JSON string
 ///
 /// This does not check:
@@ -238,15 +578,181 @@ pub fn validate_message_value_hash_chain<T: AsRef<[u8]>, U: AsRef<[u8]>>(
 /// - The signature. See ssb-verify-signatures which lets you to batch verification of signatures.
 /// - Anything to do with the `previous` message.
 pub fn validate_message_value<T: AsRef<[u8]>>(message_bytes: T) -> Result<()> {
+    validate_message_value_parsed(message_bytes)?;
+
+    Ok(())
+}
+
+/// Check whether a single message value (in isolation) is valid.
+///
+/// This performs exactly the same checks as [`validate_message_value`], for callers which only
+/// need a yes or no answer. It never panics, whatever the bytes; note that `false` does not say
+/// why the message value is invalid.
+pub fn is_valid_message_value<T: AsRef<[u8]>>(message_bytes: T) -> bool {
+    validate_message_value(message_bytes).is_ok()
+}
+
+/// Check whether a message value is valid in relation to the previous message value.
+///
+/// This performs exactly the same checks as [`validate_message_value_hash_chain`], for callers
+/// which only need a yes or no answer. It never panics, whatever the bytes; note that `false` does
+/// not say why the message value is invalid.
+pub fn is_valid_message_value_hash_chain<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+    message_bytes: T,
+    previous_msg_bytes: Option<U>,
+) -> bool {
+    validate_message_value_hash_chain(message_bytes, previous_msg_bytes).is_ok()
+}
+
+/// Validate a single message value (in isolation), returning the parsed message value on success.
+///
+/// This performs exactly the same checks as [`validate_message_value`] and is useful when the
+/// message value is needed after validation, since it avoids parsing the message a second time.
+///
+/// # Example
+///```
+///use ssb_validate::message_value::validate_message_value_parsed;
+///let valid_message_1 = r##"{
+///  "previous": null,
+///  "author": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+///  "sequence": 1,
+///  "timestamp": 1470186877575,
+///  "hash": "sha256",
+///  "content": {
+///    "type": "about",
+///    "about": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+///    "name": "Piet"
+///  },
+///  "signature": "QJKWui3oyK6r5dH13xHkEVFhfMZDTXfK2tW21nyfheFClSf69yYK77Itj1BGcOimZ16pj9u3tMArLUCGSscqCQ==.sig.ed25519"
+///}"##;
+/// let message_value = validate_message_value_parsed(valid_message_1.as_bytes()).unwrap();
+/// assert_eq!(message_value.sequence, 1);
+///```
+pub fn validate_message_value_parsed<T: AsRef<[u8]>>(message_bytes: T) -> Result<SsbMessageValue> {
+    validate_message_value_parsed_with_options(message_bytes, &ValidationOptions::default())
+}
+
+/// Validate a single message value (in isolation), performing the optional checks enabled in
+/// `options`.
+///
+/// See [`validate_message_value`].
+pub fn validate_message_value_with_options<T: AsRef<[u8]>>(
+    message_bytes: T,
+    options: &ValidationOptions,
+) -> Result<()> {
+    validate_message_value_parsed_with_options(message_bytes, options)?;
+
+    Ok(())
+}
+
+/// A construct which is valid but discouraged, reported by
+/// [`validate_message_value_with_warnings`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    /// The `timestamp` has a fractional part. Timestamps are expected to be whole milliseconds.
+    FractionalTimestamp { timestamp: f64 },
+    /// The `sequence` field comes before the `author` field. This order is accepted for
+    /// compatibility with legacy feeds, but new messages should put `author` first.
+    SequenceBeforeAuthor,
+}
+
+/// Validate a single message value (in isolation), returning any non-fatal warnings.
+///
+/// This performs exactly the same checks as [`validate_message_value`], failing in the same way.
+/// A message value which passes may still contain constructs which are valid but discouraged;
+/// these are returned as a list of [`Warning`]s, which is empty for a well-formed message value.
+///
+/// # Example
+///```
+///use ssb_validate::message_value::validate_message_value_with_warnings;
+///use ssb_validate::test_data::MESSAGE_VALUE_1;
+///let warnings = validate_message_value_with_warnings(MESSAGE_VALUE_1).unwrap();
+///assert!(warnings.is_empty());
+///```
+pub fn validate_message_value_with_warnings<T: AsRef<[u8]>>(
+    message_bytes: T,
+) -> Result<Vec<Warning>> {
     let message_bytes = message_bytes.as_ref();
-    let message_value = from_slice::<SsbMessageValue>(message_bytes).context(InvalidMessage {
-        message: message_bytes.to_owned(),
-    })?;
+    let message_value = validate_message_value_parsed(message_bytes)?;
+
+    let mut warnings = Vec::new();
+
+    let timestamp = f64::from(message_value.timestamp);
+    if timestamp.fract() != 0.0 {
+        warnings.push(Warning::FractionalTimestamp { timestamp });
+    }
+
+    let order = utils::message_value_key_order(message_bytes);
+    let position = |field: &str| order.iter().position(|key| key == field);
+    if position("sequence") < position("author") {
+        warnings.push(Warning::SequenceBeforeAuthor);
+    }
+
+    Ok(warnings)
+}
+
+/// Validate a single message value (in isolation), tolerating unexpected top-level fields.
+///
+/// The fields of a message value are normally fixed, and an unexpected field fails validation.
+/// Here, unexpected fields are removed before performing the same checks as
+/// [`validate_message_value`] on the remaining fields (so the length check does not count them).
+/// On success, the parsed message value is returned along with the names of the unexpected fields,
+/// in the order in which they appear. This is intended for studying messages of newer formats;
+/// the strict validators should be used to decide whether a message is accepted.
+///
+/// # Example
+///```
+///use ssb_validate::message_value::validate_message_value_lenient;
+///use ssb_validate::test_data::MESSAGE_VALUE_1;
+///let message = MESSAGE_VALUE_1.replace("\"hash\"", "\"extra\": true,\n  \"hash\"");
+///let (_, unexpected) = validate_message_value_lenient(message).unwrap();
+///assert_eq!(unexpected, vec!["extra".to_string()]);
+///```
+pub fn validate_message_value_lenient<T: AsRef<[u8]>>(
+    message_bytes: T,
+) -> Result<(SsbMessageValue, Vec<String>)> {
+    let message_bytes = message_bytes.as_ref();
+    let object = match from_slice::<Value>(message_bytes) {
+        Ok(Value::Object(object)) => object,
+        // Report the same error as the strict validation.
+        _ => return validate_message_value_parsed(message_bytes).map(|value| (value, Vec::new())),
+    };
+
+    let mut unexpected_fields = Vec::new();
+    // Rebuilt rather than removing fields in place, which does not preserve the order of the rest.
+    let mut expected = RidiculousStringMap::with_capacity(MESSAGE_VALUE_FIELDS.len());
+    for (field, value) in object.iter() {
+        if MESSAGE_VALUE_FIELDS.contains(&field.as_str()) {
+            expected.insert(field.to_owned(), value.clone());
+        } else {
+            unexpected_fields.push(field.to_owned());
+        }
+    }
+    if unexpected_fields.is_empty() {
+        let message_value = validate_message_value_parsed(message_bytes)?;
+        return Ok((message_value, unexpected_fields));
+    }
+
+    let expected_bytes =
+        to_vec(&Value::Object(expected), false).context(InvalidMessageCouldNotSerializeValue)?;
+    let message_value = validate_message_value_parsed(expected_bytes)?;
+
+    Ok((message_value, unexpected_fields))
+}
+
+/// Validate a single message value (in isolation), performing the optional checks enabled in
+/// `options` and returning the parsed message value on success.
+pub(crate) fn validate_message_value_parsed_with_options<T: AsRef<[u8]>>(
+    message_bytes: T,
+    options: &ValidationOptions,
+) -> Result<SsbMessageValue> {
+    let message_bytes = message_bytes.as_ref();
+    let message_value = parse_message_value(message_bytes)?;
 
     // perform common validation checks without `previous` message
-    message_value_common_checks(&message_value, None, message_bytes, None, false)?;
+    message_value_common_checks(&message_value, None, message_bytes, None, false, options)?;
 
-    Ok(())
+    Ok(message_value)
 }
 
 /// Batch validate a collection of message values. Messages are not required to be in order or to
@@ -260,10 +766,11 @@ pub fn validate_message_value<T: AsRef<[u8]>>(message_bytes: T) -> Result<()> {
 /// content: {},
 /// signature: ""
 /// }`
-pub fn par_validate_message_value<T: AsRef<[u8]>>(messages: &[T]) -> Result<()>
+#[cfg(feature = "parallel")]
+pub fn par_validate_message_value<T>(messages: &[T]) -> Result<()>
 where
     [T]: ParallelSlice<T>,
-    T: Sync,
+    T: AsRef<[u8]> + Sync,
 {
     messages
         .par_iter()
@@ -287,7 +794,8 @@ where
 ///
 /// - The fields (keys) of the message value are in the correct order
 /// - The hash signature is `sha256`
-/// - The message `content` is canonical base64 (if `content` is a string)
+/// - The timestamp is not negative
+/// - The message `content` is canonical base64 with a `.box` or `.box2` suffix (if `content` is a string)
 /// - The message value does not exceed 8192 UTF-16 code units when serialized as a JSON string
 /// - The `author` of the message value matches the `author` of the previous message value
 ///
@@ -311,15 +819,20 @@ pub fn validate_ooo_message_value_hash_chain<T: AsRef<[u8]>, U: AsRef<[u8]>>(
             )?;
             Some(previous)
         }
-        None => (None),
+        None => None,
     };
 
-    let message_value = from_slice::<SsbMessageValue>(message_bytes).context(InvalidMessage {
-        message: message_bytes.to_owned(),
-    })?;
+    let message_value = parse_message_value(message_bytes)?;
 
     // perform common validation checks without `previous` message
-    message_value_common_checks(&message_value, None, message_bytes, None, false)?;
+    message_value_common_checks(
+        &message_value,
+        None,
+        message_bytes,
+        None,
+        false,
+        &ValidationOptions::default(),
+    )?;
 
     if let Some(previous_value) = previous_value.as_ref() {
         // The authors are not allowed to change in a feed.
@@ -337,6 +850,10 @@ pub fn validate_ooo_message_value_hash_chain<T: AsRef<[u8]>, U: AsRef<[u8]>>(
 
 /// Batch validate an out-of-order collection of message values from a single author.
 ///
+/// Every message value must share the author of the first message value in the collection (and
+/// of `previous`, if given), otherwise `Error::AuthorsDidNotMatch` is returned with the offending
+/// author.
+///
 /// It expects the messages to be the JSON encoded message value of shape: `{
 /// previous: "",
 /// author: "",
@@ -345,14 +862,15 @@ pub fn validate_ooo_message_value_hash_chain<T: AsRef<[u8]>, U: AsRef<[u8]>>(
 /// content: {},
 /// signature: ""
 /// }`
-pub fn par_validate_ooo_message_value_hash_chain_of_feed<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+#[cfg(feature = "parallel")]
+pub fn par_validate_ooo_message_value_hash_chain_of_feed<T, U>(
     messages: &[T],
     previous: Option<U>,
 ) -> Result<()>
 where
     [T]: ParallelSlice<T>,
-    T: Sync,
-    U: Sync + Send + Copy,
+    T: AsRef<[u8]> + Sync,
+    U: AsRef<[u8]> + Sync + Send,
 {
     messages
         .par_iter()
@@ -361,13 +879,12 @@ where
             || (),
             |_, (idx, msg)| {
                 if idx == 0 {
-                    let prev = previous.map(|prev| prev.as_ref().to_owned());
+                    let prev = previous.as_ref().map(|prev| prev.as_ref());
                     validate_ooo_message_value_hash_chain(msg.as_ref(), prev)
                 } else {
-                    validate_ooo_message_value_hash_chain(
-                        msg.as_ref(),
-                        Some(messages[idx - 1].as_ref()),
-                    )
+                    // Since the messages are out-of-order, check against the first message
+                    // rather than the adjacent one.
+                    validate_ooo_message_value_hash_chain(msg.as_ref(), Some(messages[0].as_ref()))
                 }
             },
         )
@@ -375,115 +892,947 @@ where
 }
 
 /// Validation checks which are common across all contexts. The `check_previous` argument is used
-/// to control checks for the optional `previous_value` and `previous_key` parameters.
+/// to control checks for the optional `previous_value` and `previous_key` parameters. The
+/// `options` argument is used to enable optional checks.
+///
+/// New optional checks belong in [`ValidationOptions`] rather than in further arguments.
+/// `check_previous` is not one of them: it is set by the caller according to whether the messages
+/// are validated in order (`true`) or out-of-order (`false`), not by the user.
 pub fn message_value_common_checks(
     message_value: &SsbMessageValue,
     previous_value: Option<&SsbMessageValue>,
     message_bytes: &[u8],
     previous_key: Option<&Multihash>,
     check_previous: bool,
+    options: &ValidationOptions,
 ) -> Result<()> {
-    // The message value fields are in the correct order.
-    ensure!(
-        utils::is_correct_order(message_bytes),
-        InvalidMessageValueOrder {
-            message: message_bytes.to_owned()
-        }
-    );
-
-    // The hash signature must be `sha256`.
-    ensure!(
-        message_value.hash == "sha256",
-        InvalidHashFunction {
-            message: message_bytes.to_owned()
-        }
-    );
-
-    // The message `content` string must be canonical base64.
-    if let Value::String(private_msg) = &message_value.content.0 {
-        ensure!(
-            utils::is_canonical_base64(private_msg),
-            InvalidBase64 {
-                message: message_bytes,
-            }
-        );
+    check_order(message_bytes, options)?;
+    check_hash_function(message_value, message_bytes)?;
+    check_author_and_signature_format(message_value, message_bytes)?;
+    check_previous_reference(message_value, message_bytes)?;
+    check_timestamp(message_value, message_bytes, options)?;
+    if !options.skip_content_checks {
+        check_private_content(message_value, message_bytes)?;
+        check_ciphertext_length(message_value, message_bytes, options)?;
+        check_content_depth(message_value, message_bytes, options)?;
+        check_content_links(message_value, message_bytes, options)?;
+        check_about_link(message_value, message_bytes, options)?;
+        check_blob_links(message_value, message_bytes, options)?;
+        check_content_type_length(message_value, message_bytes, options)?;
+        check_vote_content(message_value, message_bytes, options)?;
+        check_contact_content(message_value, message_bytes, options)?;
     }
 
     if check_previous {
         if let Some(previous_value) = previous_value {
-            // The authors are not allowed to change in a feed.
-            ensure!(
-                message_value.author == previous_value.author,
-                AuthorsDidNotMatch {
-                    previous_author: previous_value.author.clone(),
-                    author: message_value.author.clone()
-                }
-            );
-
-            // The sequence must increase by one.
-            let expected_sequence = previous_value.sequence + 1;
-            ensure!(
-                message_value.sequence == expected_sequence,
-                InvalidSequenceNumber {
-                    message: message_bytes.to_owned(),
-                    actual: message_value.sequence,
-                    expected: expected_sequence
-                }
-            );
-
-            // msg previous must match hash of previous.value otherwise it's a fork.
-            ensure!(
-                message_value.previous.as_ref().context(PreviousWasNull)?
-                    == previous_key.expect("expected the previous key to be Some(key), was None"),
-                ForkedFeed {
-                    previous_seq: previous_value.sequence
-                }
-            );
+            check_author(message_value, previous_value)?;
+            check_sequence(message_value, previous_value, message_bytes)?;
+            check_monotonic_timestamp(message_value, previous_value, options)?;
+            check_fork(message_value, previous_value, previous_key)?;
         } else {
             // This message is the first message.
-
-            // Sequence must be 1.
-            ensure!(
-                message_value.sequence == 1,
-                FirstMessageDidNotHaveSequenceOfOne {
-                    message: message_bytes.to_owned()
-                }
-            );
-            // Previous must be None.
-            ensure!(
-                message_value.previous.is_none(),
-                FirstMessageDidNotHavePreviousOfNull {
-                    message: message_bytes.to_owned()
-                }
-            );
+            check_first_sequence(message_value, message_bytes)?;
+            check_first_previous(message_value, message_bytes)?;
         };
     }
+    check_positive_sequence(message_value, message_bytes)?;
+    if !check_previous {
+        check_first_message_markers(message_value, message_bytes)?;
+    }
 
-    // The message `value` length must be less than 8192 UTF-16 code units.
     // We check this last since serialization is expensive.
-    ensure!(
-        utils::is_correct_length(message_value)?,
-        InvalidMessageValueLength {
-            message: message_bytes.to_owned()
-        }
-    );
+    check_length(message_value, message_bytes, options)?;
 
     Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::message_value::{
-        par_validate_message_value, par_validate_message_value_hash_chain_of_feed,
-        par_validate_ooo_message_value_hash_chain_of_feed, validate_message_value,
-        validate_message_value_hash_chain, validate_ooo_message_value_hash_chain,
-    };
-    use crate::test_data::{
-        MESSAGE_VALUE_1, MESSAGE_VALUE_2, MESSAGE_VALUE_3, MESSAGE_VALUE_3_INCORRECT_AUTHOR,
-    };
-
-    #[test]
-    fn it_works_first_message_value() {
+/// Lint a message value, collecting every validation problem instead of stopping at the first.
+///
+/// It expects the messages to be the JSON encoded message value of shape: `{
+/// previous: "",
+/// author: "",
+/// sequence: ...,
+/// timestamp: ...,
+/// content: {},
+/// signature: ""
+/// }`
+///
+/// Each of the checks of [`validate_message_value_hash_chain`] is run independently, and all of
+/// the failures are returned (an empty vector means the message value is valid). This is intended
+/// for tools which show the author of a message everything that is wrong with it. If the message
+/// value (or the previous message value) cannot be decoded, no further checks can run and only the
+/// decoding error is returned.
+///
+/// `previous_msg_bytes` will be `None` only when `message_bytes` is the first message by that author.
+pub fn lint_message_value<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+    message_bytes: T,
+    previous_msg_bytes: Option<U>,
+) -> Vec<Error> {
+    let message_bytes = message_bytes.as_ref();
+    let options = ValidationOptions::default();
+
+    let previous = match previous_msg_bytes {
+        Some(message) => {
+            match from_slice::<SsbMessageValue>(message.as_ref()).context(InvalidPreviousMessage {
+                message: message.as_ref().to_owned(),
+            }) {
+                Ok(previous) => Some((previous, utils::multihash_from_bytes(message.as_ref()))),
+                Err(err) => return vec![err],
+            }
+        }
+        None => None,
+    };
+
+    let message_value = match parse_message_value(message_bytes) {
+        Ok(message_value) => message_value,
+        Err(err) => return vec![err],
+    };
+
+    let mut results = vec![
+        check_order(message_bytes, &options),
+        check_hash_function(&message_value, message_bytes),
+        check_author_and_signature_format(&message_value, message_bytes),
+        check_previous_reference(&message_value, message_bytes),
+        check_timestamp(&message_value, message_bytes, &options),
+        check_positive_sequence(&message_value, message_bytes),
+        check_private_content(&message_value, message_bytes),
+    ];
+    match &previous {
+        Some((previous_value, previous_key)) => {
+            results.push(check_author(&message_value, previous_value));
+            results.push(check_sequence(
+                &message_value,
+                previous_value,
+                message_bytes,
+            ));
+            results.push(check_fork(
+                &message_value,
+                previous_value,
+                Some(previous_key),
+            ));
+        }
+        None => {
+            results.push(check_first_sequence(&message_value, message_bytes));
+            results.push(check_first_previous(&message_value, message_bytes));
+            results.push(check_first_message_markers(&message_value, message_bytes));
+        }
+    }
+    results.push(check_length(&message_value, message_bytes, &options));
+
+    results.into_iter().filter_map(Result::err).collect()
+}
+
+/// The outcome of each individual check of a message value (see [`debug_report`]).
+///
+/// `Some(true)` means the check passed, `Some(false)` that it failed and `None` that it was not
+/// run (because the message value could not be decoded, or no previous message was supplied).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// The message value could be decoded.
+    pub decoded: bool,
+    /// The fields are in the correct order.
+    pub order: bool,
+    /// The `hash` is `sha256`.
+    pub hash: Option<bool>,
+    /// The `author` is a feed id and the `signature` an ed25519 signature.
+    pub format: Option<bool>,
+    /// The `sequence` is at least 1.
+    pub sequence: Option<bool>,
+    /// The `previous` is `null` if, and only if, the `sequence` is 1.
+    pub first_message_markers: Option<bool>,
+    /// Private (string) content is canonical base64.
+    pub base64: Option<bool>,
+    /// The serialized message value is not too long.
+    pub length: Option<bool>,
+    /// The `author`, `sequence` and `previous` follow on from the previous message.
+    pub continuity: Option<bool>,
+}
+
+/// Report the outcome of each check of a message value individually.
+///
+/// It expects the messages to be the JSON encoded message value of shape: `{
+/// previous: "",
+/// author: "",
+/// sequence: ...,
+/// timestamp: ...,
+/// content: {},
+/// signature: ""
+/// }`
+///
+/// This is a diagnostic tool for comparing this crate with other implementations check by check.
+/// Unlike [`lint_message_value`], which returns the errors, the report records a verdict for every
+/// check, including those which passed. The continuity check is only run when
+/// `previous_msg_bytes` is supplied, and fails if the previous message value cannot be decoded.
+///
+/// # Example
+///```
+///use ssb_validate::message_value::debug_report;
+///use ssb_validate::test_data::{MESSAGE_VALUE_1, MESSAGE_VALUE_2};
+///let report = debug_report(MESSAGE_VALUE_2, Some(MESSAGE_VALUE_1));
+///assert!(report.decoded && report.order);
+///assert_eq!(report.continuity, Some(true));
+///```
+pub fn debug_report<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+    message_bytes: T,
+    previous_msg_bytes: Option<U>,
+) -> ValidationReport {
+    let message_bytes = message_bytes.as_ref();
+    let options = ValidationOptions::default();
+
+    let order = check_order(message_bytes, &options).is_ok();
+    let message_value = match parse_message_value(message_bytes) {
+        Ok(message_value) => message_value,
+        Err(_) => {
+            return ValidationReport {
+                decoded: false,
+                order,
+                hash: None,
+                format: None,
+                sequence: None,
+                first_message_markers: None,
+                base64: None,
+                length: None,
+                continuity: None,
+            }
+        }
+    };
+
+    let continuity = previous_msg_bytes.map(|previous| {
+        let previous = previous.as_ref();
+        match from_slice::<SsbMessageValue>(previous) {
+            Ok(previous_value) => {
+                let previous_key = utils::multihash_from_bytes(previous);
+                check_author(&message_value, &previous_value).is_ok()
+                    && check_sequence(&message_value, &previous_value, message_bytes).is_ok()
+                    && check_fork(&message_value, &previous_value, Some(&previous_key)).is_ok()
+            }
+            Err(_) => false,
+        }
+    });
+
+    ValidationReport {
+        decoded: true,
+        order,
+        hash: Some(check_hash_function(&message_value, message_bytes).is_ok()),
+        format: Some(check_author_and_signature_format(&message_value, message_bytes).is_ok()),
+        sequence: Some(check_positive_sequence(&message_value, message_bytes).is_ok()),
+        first_message_markers: Some(
+            check_first_message_markers(&message_value, message_bytes).is_ok(),
+        ),
+        base64: Some(check_private_content(&message_value, message_bytes).is_ok()),
+        length: Some(check_length(&message_value, message_bytes, &options).is_ok()),
+        continuity,
+    }
+}
+
+/// Check that a message value follows on from the previous message value of its feed.
+///
+/// Only the relationship between the two messages is checked: the `author` must not change, the
+/// `sequence` must increase by one and the `previous` must be `previous_key`. None of the checks
+/// which apply to a message in isolation (order, hash function, length, etc.) are performed, so
+/// this is intended for messages which have already been validated individually (for example,
+/// with [`validate_ooo_message_value_hash_chain`]).
+///
+/// # Example
+///```
+///use ssb_validate::message_value::{chains_from, SsbMessageValue};
+///use ssb_validate::test_data::{MESSAGE_VALUE_1, MESSAGE_VALUE_2};
+///use ssb_validate::utils::multihash_from_bytes;
+///use std::convert::TryFrom;
+///
+///let previous = SsbMessageValue::try_from(MESSAGE_VALUE_1).unwrap();
+///let current = SsbMessageValue::try_from(MESSAGE_VALUE_2).unwrap();
+///let previous_key = multihash_from_bytes(MESSAGE_VALUE_1.as_bytes());
+///assert!(chains_from(&current, &previous, &previous_key).is_ok());
+///assert!(chains_from(&previous, &current, &previous_key).is_err());
+///```
+pub fn chains_from(
+    current: &SsbMessageValue,
+    previous: &SsbMessageValue,
+    previous_key: &Multihash,
+) -> Result<()> {
+    check_author(current, previous)?;
+    // Only serialize the message value (for the error) if the sequence is wrong.
+    check_sequence(current, previous, &[]).or_else(|_| {
+        let message_bytes = utils::canonical_value_bytes(current)?;
+        check_sequence(current, previous, &message_bytes)
+    })?;
+    check_fork(current, previous, Some(previous_key))
+}
+
+/// The message value fields are in the correct order (or an order tolerated by the JavaScript
+/// implementation, if lenient).
+fn check_order(message_bytes: &[u8], options: &ValidationOptions) -> Result<()> {
+    let is_order_accepted = if options.lenient_order {
+        utils::is_legacy_js_order(message_bytes)
+    } else {
+        utils::is_correct_order(message_bytes)
+    };
+
+    ensure!(
+        is_order_accepted,
+        InvalidMessageValueOrder {
+            message: message_bytes.to_owned(),
+            found_order: utils::message_value_key_order(message_bytes)
+        }
+    );
+
+    Ok(())
+}
+
+/// The hash signature must be `sha256`.
+fn check_hash_function(message_value: &SsbMessageValue, message_bytes: &[u8]) -> Result<()> {
+    ensure!(
+        message_value.hash == "sha256",
+        InvalidHashFunction {
+            message: message_bytes.to_owned()
+        }
+    );
+
+    Ok(())
+}
+
+/// The `author` must be an ed25519 feed id whose base64 body decodes to a 32-byte key, and the
+/// `signature` must be an ed25519 signature whose base64 body decodes to 64 bytes.
+fn check_author_and_signature_format(
+    message_value: &SsbMessageValue,
+    message_bytes: &[u8],
+) -> Result<()> {
+    message_value.feed_id()?;
+
+    let signature_length = message_value
+        .signature
+        .strip_suffix(".sig.ed25519")
+        .and_then(|encoded| base64::decode(encoded).ok())
+        .map(|signature| signature.len());
+    ensure!(
+        signature_length == Some(64),
+        InvalidSignatureFormat {
+            message: message_bytes.to_owned()
+        }
+    );
+
+    Ok(())
+}
+
+/// The `previous` reference must be a message hash (`%...sha256`), not a blob hash.
+fn check_previous_reference(message_value: &SsbMessageValue, message_bytes: &[u8]) -> Result<()> {
+    ensure!(
+        !matches!(message_value.previous, Some(Multihash::Blob(_))),
+        InvalidPreviousReference {
+            message: message_bytes.to_owned()
+        }
+    );
+
+    Ok(())
+}
+
+/// The timestamp must not be negative (`LegacyF64` already rules out non-finite values), nor
+/// fractional (if enabled).
+fn check_timestamp(
+    message_value: &SsbMessageValue,
+    message_bytes: &[u8],
+    options: &ValidationOptions,
+) -> Result<()> {
+    let timestamp = f64::from(message_value.timestamp);
+    ensure!(
+        timestamp >= 0.0 && (!options.require_integer_timestamp || timestamp.fract() == 0.0),
+        InvalidTimestamp {
+            message: message_bytes.to_owned()
+        }
+    );
+
+    Ok(())
+}
+
+/// The message `content` string must be canonical base64 with a `.box` or `.box2` suffix.
+fn check_private_content(message_value: &SsbMessageValue, message_bytes: &[u8]) -> Result<()> {
+    if let Value::String(private_msg) = &message_value.content.0 {
+        let is_canonical = match utils::encryption_scheme(private_msg) {
+            Some(EncScheme::Box1) => utils::is_canonical_base64(private_msg),
+            Some(EncScheme::Box2) => utils::is_canonical_base64_box2(private_msg),
+            None => false,
+        };
+        ensure!(
+            is_canonical,
+            InvalidBase64 {
+                message: message_bytes,
+            }
+        );
+    }
+
+    Ok(())
+}
+
+/// The ciphertext of `.box` content must be long enough to be a private-box (if enabled).
+fn check_ciphertext_length(
+    message_value: &SsbMessageValue,
+    message_bytes: &[u8],
+    options: &ValidationOptions,
+) -> Result<()> {
+    if !options.validate_ciphertext_length {
+        return Ok(());
+    }
+
+    if let Value::String(private_msg) = &message_value.content.0 {
+        if let Some(encoded) = private_msg.strip_suffix(".box") {
+            // The encoding has already been checked by `check_private_content`.
+            let length = base64::decode(encoded).map_or(0, |ciphertext| ciphertext.len());
+            ensure!(
+                length >= MIN_PRIVATE_BOX_LENGTH,
+                CiphertextTooShort {
+                    message: message_bytes.to_owned(),
+                    length,
+                    minimum: MIN_PRIVATE_BOX_LENGTH,
+                }
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// The message `content` must not be nested too deeply (if enabled).
+fn check_content_depth(
+    message_value: &SsbMessageValue,
+    message_bytes: &[u8],
+    options: &ValidationOptions,
+) -> Result<()> {
+    if let Some(max_depth) = options.max_content_depth {
+        ensure!(
+            !utils::is_deeper_than(&message_value.content.0, max_depth),
+            ContentTooDeep {
+                message: message_bytes.to_owned(),
+                max_depth
+            }
+        );
+    }
+
+    Ok(())
+}
+
+/// The `link` of each of the `mentions` in the message `content` must be valid (if enabled).
+fn check_content_links(
+    message_value: &SsbMessageValue,
+    message_bytes: &[u8],
+    options: &ValidationOptions,
+) -> Result<()> {
+    if options.validate_links {
+        check_mention_links(&message_value.content.0, message_bytes)?;
+    }
+
+    Ok(())
+}
+
+/// The `type` of object content must be between 3 and 52 UTF-8 bytes (if enabled).
+fn check_content_type_length(
+    message_value: &SsbMessageValue,
+    message_bytes: &[u8],
+    options: &ValidationOptions,
+) -> Result<()> {
+    if !options.validate_content_type_length {
+        return Ok(());
+    }
+
+    if let Value::Object(content) = &message_value.content.0 {
+        if let Some(Value::String(content_type)) = content.get("type") {
+            ensure!(
+                (3..=52).contains(&content_type.len()),
+                InvalidContentType {
+                    message: message_bytes.to_owned(),
+                    content_type: content_type.to_owned()
+                }
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// The `about` of an `about` message must be a feed or message reference (if enabled).
+fn check_about_link(
+    message_value: &SsbMessageValue,
+    message_bytes: &[u8],
+    options: &ValidationOptions,
+) -> Result<()> {
+    if !options.validate_about_links {
+        return Ok(());
+    }
+
+    let content = match &message_value.content.0 {
+        Value::Object(content) => content,
+        _ => return Ok(()),
+    };
+    match (content.get("type"), content.get("about")) {
+        (Some(Value::String(content_type)), Some(about)) if content_type == "about" => {
+            let about = match about {
+                Value::String(about) => about.to_owned(),
+                about => to_string(about, true).unwrap_or_default(),
+            };
+            // A blob is not something which can be described by an `about` message.
+            ensure!(
+                (about.starts_with('@') && utils::is_valid_link(&about))
+                    || utils::is_message_hash(&about),
+                InvalidLink {
+                    message: message_bytes.to_owned(),
+                    link: about
+                }
+            );
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Links to media in the message `content` must be blob references (if enabled).
+fn check_blob_links(
+    message_value: &SsbMessageValue,
+    message_bytes: &[u8],
+    options: &ValidationOptions,
+) -> Result<()> {
+    if !options.validate_blob_links {
+        return Ok(());
+    }
+
+    let content = match &message_value.content.0 {
+        Value::Object(content) => content,
+        _ => return Ok(()),
+    };
+
+    let image_link = match content.get("image") {
+        Some(Value::Object(image)) => image.get("link"),
+        image => image,
+    };
+    // Only mentions with a MIME type (such as `image/png`) are links to media.
+    let mention_links = match content.get("mentions") {
+        Some(Value::Array(mentions)) => mentions
+            .iter()
+            .filter_map(|mention| match mention {
+                Value::Object(mention) => match mention.get("type") {
+                    Some(Value::String(mime_type)) if mime_type.contains('/') => {
+                        mention.get("link")
+                    }
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    for link in image_link.into_iter().chain(mention_links) {
+        let link = match link {
+            Value::String(link) => link.to_owned(),
+            link => to_string(link, true).unwrap_or_default(),
+        };
+        ensure!(
+            utils::is_blob_hash(&link),
+            InvalidLink {
+                message: message_bytes.to_owned(),
+                link
+            }
+        );
+    }
+
+    Ok(())
+}
+
+/// The `vote` of a `vote` message must link to a message and have an integer value (if enabled).
+fn check_vote_content(
+    message_value: &SsbMessageValue,
+    message_bytes: &[u8],
+    options: &ValidationOptions,
+) -> Result<()> {
+    if !options.validate_vote_content {
+        return Ok(());
+    }
+
+    let content = match &message_value.content.0 {
+        Value::Object(content) => content,
+        _ => return Ok(()),
+    };
+    match content.get("type") {
+        Some(Value::String(content_type)) if content_type == "vote" => {}
+        _ => return Ok(()),
+    }
+
+    let vote = match content.get("vote") {
+        Some(Value::Object(vote)) => vote,
+        _ => {
+            return InvalidContent {
+                message: message_bytes.to_owned(),
+                reason: "vote must be an object",
+            }
+            .fail()
+        }
+    };
+    ensure!(
+        matches!(vote.get("link"), Some(Value::String(link)) if utils::is_message_hash(link)),
+        InvalidContent {
+            message: message_bytes.to_owned(),
+            reason: "vote link must be a message reference",
+        }
+    );
+    ensure!(
+        matches!(vote.get("value"), Some(Value::Float(value)) if f64::from(*value).fract() == 0.0),
+        InvalidContent {
+            message: message_bytes.to_owned(),
+            reason: "vote value must be an integer",
+        }
+    );
+
+    Ok(())
+}
+
+/// The `contact` of a `contact` message must be a feed reference and its `following` and
+/// `blocking` (when present) must be booleans (if enabled).
+fn check_contact_content(
+    message_value: &SsbMessageValue,
+    message_bytes: &[u8],
+    options: &ValidationOptions,
+) -> Result<()> {
+    if !options.validate_contact_content {
+        return Ok(());
+    }
+
+    let content = match &message_value.content.0 {
+        Value::Object(content) => content,
+        _ => return Ok(()),
+    };
+    match content.get("type") {
+        Some(Value::String(content_type)) if content_type == "contact" => {}
+        _ => return Ok(()),
+    }
+
+    ensure!(
+        matches!(content.get("contact"), Some(Value::String(contact)) if contact.starts_with('@') && utils::is_valid_link(contact)),
+        InvalidContent {
+            message: message_bytes.to_owned(),
+            reason: "contact must be a feed reference",
+        }
+    );
+    ensure!(
+        matches!(content.get("following"), None | Some(Value::Bool(_))),
+        InvalidContent {
+            message: message_bytes.to_owned(),
+            reason: "contact following must be a boolean",
+        }
+    );
+    ensure!(
+        matches!(content.get("blocking"), None | Some(Value::Bool(_))),
+        InvalidContent {
+            message: message_bytes.to_owned(),
+            reason: "contact blocking must be a boolean",
+        }
+    );
+
+    Ok(())
+}
+
+/// The authors are not allowed to change in a feed.
+fn check_author(message_value: &SsbMessageValue, previous_value: &SsbMessageValue) -> Result<()> {
+    ensure!(
+        message_value.author == previous_value.author,
+        AuthorsDidNotMatch {
+            previous_author: previous_value.author.clone(),
+            author: message_value.author.clone()
+        }
+    );
+
+    Ok(())
+}
+
+/// The sequence must increase by one.
+fn check_sequence(
+    message_value: &SsbMessageValue,
+    previous_value: &SsbMessageValue,
+    message_bytes: &[u8],
+) -> Result<()> {
+    let expected_sequence = previous_value
+        .sequence
+        .checked_add(1)
+        .context(SequenceOverflow {
+            message: message_bytes.to_owned(),
+        })?;
+    ensure!(
+        message_value.sequence == expected_sequence,
+        InvalidSequenceNumber {
+            message: message_bytes.to_owned(),
+            actual: message_value.sequence,
+            expected: expected_sequence
+        }
+    );
+
+    Ok(())
+}
+
+/// The timestamp must not decrease (if enabled).
+fn check_monotonic_timestamp(
+    message_value: &SsbMessageValue,
+    previous_value: &SsbMessageValue,
+    options: &ValidationOptions,
+) -> Result<()> {
+    // Compare as `f64` since the `Ord` impl of `LegacyF64` recurses infinitely.
+    if options.enforce_monotonic_timestamps {
+        let previous_timestamp = f64::from(previous_value.timestamp);
+        let timestamp = f64::from(message_value.timestamp);
+        ensure!(
+            timestamp >= previous_timestamp,
+            NonMonotonicTimestamp {
+                previous: previous_timestamp,
+                current: timestamp
+            }
+        );
+    }
+
+    Ok(())
+}
+
+/// msg previous must match hash of previous.value otherwise it's a fork.
+fn check_fork(
+    message_value: &SsbMessageValue,
+    previous_value: &SsbMessageValue,
+    previous_key: Option<&Multihash>,
+) -> Result<()> {
+    ensure!(
+        message_value.previous.as_ref().context(PreviousWasNull)?
+            == previous_key.expect("expected the previous key to be Some(key), was None"),
+        ForkedFeed {
+            previous_seq: previous_value.sequence
+        }
+    );
+
+    Ok(())
+}
+
+/// The author, sequence and previous of a message must continue the feed from a summary of the
+/// previous message (its key, sequence and author).
+pub(crate) fn check_continuity_with_summary(
+    message_value: &SsbMessageValue,
+    message_bytes: &[u8],
+    previous_key: &Multihash,
+    previous_seq: u64,
+    previous_author: &str,
+) -> Result<()> {
+    // The authors are not allowed to change in a feed.
+    ensure!(
+        message_value.author == previous_author,
+        AuthorsDidNotMatch {
+            previous_author: previous_author.to_owned(),
+            author: message_value.author.clone()
+        }
+    );
+
+    // The sequence must increase by one.
+    let expected_sequence = previous_seq.checked_add(1).context(SequenceOverflow {
+        message: message_bytes.to_owned(),
+    })?;
+    ensure!(
+        message_value.sequence == expected_sequence,
+        InvalidSequenceNumber {
+            message: message_bytes.to_owned(),
+            actual: message_value.sequence,
+            expected: expected_sequence
+        }
+    );
+
+    // msg previous must match the previous key otherwise it's a fork.
+    ensure!(
+        message_value.previous.as_ref().context(PreviousWasNull)? == previous_key,
+        ForkedFeed { previous_seq }
+    );
+
+    Ok(())
+}
+
+/// The sequence of the first message must be 1.
+fn check_first_sequence(message_value: &SsbMessageValue, message_bytes: &[u8]) -> Result<()> {
+    ensure!(
+        message_value.sequence == 1,
+        FirstMessageDidNotHaveSequenceOfOne {
+            message: message_bytes.to_owned()
+        }
+    );
+
+    Ok(())
+}
+
+/// The previous of the first message must be None.
+fn check_first_previous(message_value: &SsbMessageValue, message_bytes: &[u8]) -> Result<()> {
+    ensure!(
+        message_value.previous.is_none(),
+        FirstMessageDidNotHavePreviousOfNull {
+            message: message_bytes.to_owned()
+        }
+    );
+
+    Ok(())
+}
+
+/// The sequence must be at least 1, wherever the message is in its feed.
+fn check_positive_sequence(message_value: &SsbMessageValue, message_bytes: &[u8]) -> Result<()> {
+    ensure!(
+        message_value.sequence >= 1,
+        SequenceWasZero {
+            message: message_bytes.to_owned()
+        }
+    );
+
+    Ok(())
+}
+
+/// The `previous` must be None if, and only if, the sequence is 1. Used when the position of the
+/// message in its feed is not checked against a previous message.
+fn check_first_message_markers(
+    message_value: &SsbMessageValue,
+    message_bytes: &[u8],
+) -> Result<()> {
+    ensure!(
+        (message_value.sequence == 1) == message_value.previous.is_none(),
+        InconsistentFirstMessageMarkers {
+            message: message_bytes.to_owned()
+        }
+    );
+
+    Ok(())
+}
+
+/// The message `value` length must not exceed the limit (8192 UTF-16 code units by default, or
+/// the limit for the content `type`).
+fn check_length(
+    message_value: &SsbMessageValue,
+    message_bytes: &[u8],
+    options: &ValidationOptions,
+) -> Result<()> {
+    if options.skip_length_check {
+        return Ok(());
+    }
+
+    let limit = match &message_value.content.0 {
+        Value::Object(content) => match content.get("type") {
+            Some(Value::String(content_type)) => options.per_type_max_length.get(content_type),
+            _ => None,
+        },
+        _ => None,
+    }
+    .copied()
+    .unwrap_or(options.max_value_length);
+
+    let length = utils::message_value_length(message_value)?;
+    ensure!(
+        length <= limit,
+        InvalidMessageValueLength {
+            message: message_bytes.to_owned(),
+            actual: length,
+            limit
+        }
+    );
+
+    Ok(())
+}
+
+/// Check that the `link` of each of the `mentions` in the given message `content` is a valid
+/// reference. Content which is not an object, or has no `mentions` array, passes.
+fn check_mention_links(content: &Value, message_bytes: &[u8]) -> Result<()> {
+    let mentions = match content {
+        Value::Object(content) => match content.get("mentions") {
+            Some(Value::Array(mentions)) => mentions,
+            _ => return Ok(()),
+        },
+        _ => return Ok(()),
+    };
+
+    for mention in mentions {
+        let link = match mention {
+            Value::Object(mention) => mention.get("link"),
+            _ => None,
+        };
+        match link {
+            Some(Value::String(link)) => ensure!(
+                utils::is_valid_link(link),
+                InvalidLink {
+                    message: message_bytes.to_owned(),
+                    link: link.to_owned()
+                }
+            ),
+            // A `link` which is not a string cannot be a valid reference.
+            Some(link) => {
+                return InvalidLink {
+                    message: message_bytes.to_owned(),
+                    link: to_string(link, true).unwrap_or_default(),
+                }
+                .fail()
+            }
+            None => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::error::Error;
+    use crate::message::SsbMessage;
+    #[cfg(feature = "serde_json")]
+    use crate::message_value::validate_message_value_from_json;
+    use crate::message_value::{
+        chains_from, debug_report, is_valid_message_value, is_valid_message_value_hash_chain,
+        lint_message_value, validate_message_value, validate_message_value_hash_chain,
+        validate_message_value_hash_chain_with_options,
+        validate_message_value_hash_chain_with_previous, validate_message_value_lenient,
+        validate_message_value_parsed, validate_message_value_with_options,
+        validate_message_value_with_warnings, validate_ooo_message_value_hash_chain, Previous,
+        SsbMessageValue, ValidationReport, Warning,
+    };
+    #[cfg(feature = "parallel")]
+    use crate::message_value::{
+        par_validate_message_value, par_validate_message_value_hash_chain_of_feed,
+        par_validate_message_value_hash_chain_of_feed_in_pool,
+        par_validate_ooo_message_value_hash_chain_of_feed,
+    };
+    use crate::options::ValidationOptions;
+    #[cfg(feature = "parallel")]
+    use crate::test_data::MESSAGE_VALUE_3_INCORRECT_AUTHOR;
+    use crate::test_data::{
+        MESSAGE_PRIVATE, MESSAGE_PRIVATE_PREV, MESSAGE_VALUE_1, MESSAGE_VALUE_2, MESSAGE_VALUE_3,
+        MESSAGE_VALUE_PRIVATE_BOX2,
+    };
+    use crate::utils;
+    use ssb_legacy_msg_data::{
+        json::{from_slice, to_vec},
+        value::{ContentValue, Value},
+    };
+    use ssb_multiformats::multihash::Multihash;
+
+    #[test]
+    fn it_detects_a_leading_bom() {
+        let mut message = b"\xef\xbb\xbf".to_vec();
+        message.extend_from_slice(MESSAGE_VALUE_1.as_bytes());
+        match validate_message_value(&message) {
+            Err(Error::LeadingBom { message: _ }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_parses_the_feed_id() {
+        let mut value = from_slice::<SsbMessageValue>(MESSAGE_VALUE_1.as_bytes()).unwrap();
+        let feed_id = value.feed_id().unwrap();
+        assert_eq!(feed_id.to_legacy_string(), value.author);
+
+        value.author.push_str("extra");
+        match value.feed_id() {
+            Err(Error::InvalidAuthorFormat { author: _ }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_works_first_message_value() {
         assert!(
             validate_message_value_hash_chain::<_, &[u8]>(MESSAGE_VALUE_1.as_bytes(), None).is_ok()
         );
@@ -498,14 +1847,43 @@ mod tests {
         .is_ok());
     }
 
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn it_validates_an_ordered_sequence_of_message_values_in_parallel() {
+        let messages = [
+            MESSAGE_VALUE_1.as_bytes(),
+            MESSAGE_VALUE_2.as_bytes(),
+            MESSAGE_VALUE_3.as_bytes(),
+        ];
+        let result = par_validate_message_value_hash_chain_of_feed::<_, &[u8]>(&messages[..], None);
+        assert!(result.is_ok());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn it_validates_an_empty_batch_of_message_values() {
+        let empty: [&[u8]; 0] = [];
+        assert!(par_validate_message_value_hash_chain_of_feed::<_, &[u8]>(&empty, None).is_ok());
+        assert!(
+            par_validate_message_value_hash_chain_of_feed(&empty, Some(MESSAGE_VALUE_1)).is_ok()
+        );
+    }
+
+    #[cfg(feature = "parallel")]
     #[test]
-    fn it_validates_an_ordered_sequence_of_message_values_in_parallel() {
+    fn it_validates_message_values_in_a_given_thread_pool() {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
         let messages = [
             MESSAGE_VALUE_1.as_bytes(),
             MESSAGE_VALUE_2.as_bytes(),
             MESSAGE_VALUE_3.as_bytes(),
         ];
-        let result = par_validate_message_value_hash_chain_of_feed::<_, &[u8]>(&messages[..], None);
+        let result = par_validate_message_value_hash_chain_of_feed_in_pool::<_, &[u8]>(
+            &pool, &messages, None,
+        );
         assert!(result.is_ok());
     }
 
@@ -514,6 +1892,748 @@ mod tests {
         assert!(validate_message_value(MESSAGE_VALUE_2.as_bytes()).is_ok());
     }
 
+    #[test]
+    fn it_returns_the_parsed_message_value() {
+        let message_value = validate_message_value_parsed(MESSAGE_VALUE_2.as_bytes()).unwrap();
+        assert_eq!(message_value.sequence, 2);
+        assert_eq!(
+            message_value.author,
+            "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519"
+        );
+    }
+
+    #[test]
+    fn it_lints_every_problem_with_a_message_value() {
+        assert!(lint_message_value(MESSAGE_VALUE_2, Some(MESSAGE_VALUE_1)).is_empty());
+
+        let message = MESSAGE_VALUE_2
+            .replace("\"sha256\"", "\"sha512\"")
+            .replace("\"sequence\": 2", "\"sequence\": 3");
+        let errors = lint_message_value(message, Some(MESSAGE_VALUE_1));
+        assert_eq!(errors.len(), 2);
+        match &errors[..] {
+            [Error::InvalidHashFunction { message: _ }, Error::InvalidSequenceNumber {
+                message: _,
+                actual: 3,
+                expected: 2,
+            }] => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_reports_each_check_of_a_message_value() {
+        let report = debug_report::<_, &[u8]>(MESSAGE_VALUE_1, None);
+        assert_eq!(
+            report,
+            ValidationReport {
+                decoded: true,
+                order: true,
+                hash: Some(true),
+                format: Some(true),
+                sequence: Some(true),
+                first_message_markers: Some(true),
+                base64: Some(true),
+                length: Some(true),
+                continuity: None,
+            }
+        );
+
+        let message = MESSAGE_VALUE_2
+            .replace("\"sha256\"", "\"sha512\"")
+            .replace("\"sequence\": 2", "\"sequence\": 3");
+        let report = debug_report(message, Some(MESSAGE_VALUE_1));
+        assert_eq!(report.hash, Some(false));
+        assert_eq!(report.base64, Some(true));
+        assert_eq!(report.continuity, Some(false));
+
+        let report = debug_report::<_, &[u8]>("{}", None);
+        assert!(!report.decoded);
+        assert_eq!(report.hash, None);
+    }
+
+    #[test]
+    fn it_detects_inconsistent_first_message_markers_out_of_order() {
+        let first_with_previous = MESSAGE_VALUE_1.replace(
+            "\"previous\": null",
+            "\"previous\": \"%/v5mCnV/kmnVtnF3zXtD4tbzoEQo4kRq/0d/bgxP1WI=.sha256\"",
+        );
+        let later_without_previous = MESSAGE_VALUE_2.replace(
+            "\"previous\": \"%/v5mCnV/kmnVtnF3zXtD4tbzoEQo4kRq/0d/bgxP1WI=.sha256\"",
+            "\"previous\": null",
+        );
+        for message in [first_with_previous, later_without_previous] {
+            match validate_ooo_message_value_hash_chain::<_, &[u8]>(&message, None) {
+                Err(Error::InconsistentFirstMessageMarkers { .. }) => {}
+                _ => panic!(),
+            }
+        }
+        assert!(validate_ooo_message_value_hash_chain::<_, &[u8]>(MESSAGE_VALUE_2, None).is_ok());
+    }
+
+    #[test]
+    fn it_checks_that_message_values_chain() {
+        let first = SsbMessageValue::try_from(MESSAGE_VALUE_1).unwrap();
+        let second = SsbMessageValue::try_from(MESSAGE_VALUE_2).unwrap();
+        let third = SsbMessageValue::try_from(MESSAGE_VALUE_3).unwrap();
+        let first_key = utils::multihash_from_bytes(MESSAGE_VALUE_1.as_bytes());
+        let second_key = utils::multihash_from_bytes(MESSAGE_VALUE_2.as_bytes());
+
+        assert!(chains_from(&second, &first, &first_key).is_ok());
+        assert!(chains_from(&third, &second, &second_key).is_ok());
+
+        match chains_from(&third, &first, &first_key) {
+            Err(Error::InvalidSequenceNumber {
+                message,
+                actual: 3,
+                expected: 2,
+            }) => assert!(!message.is_empty()),
+            _ => panic!(),
+        }
+        let mut forked = third.clone();
+        forked.sequence = 2;
+        match chains_from(&forked, &first, &first_key) {
+            Err(Error::ForkedFeed { previous_seq: 1 }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn is_valid_message_value_is_total() {
+        assert!(is_valid_message_value(MESSAGE_VALUE_1));
+        assert!(is_valid_message_value_hash_chain(
+            MESSAGE_VALUE_2,
+            Some(MESSAGE_VALUE_1)
+        ));
+        assert!(!is_valid_message_value_hash_chain(
+            MESSAGE_VALUE_3,
+            Some(MESSAGE_VALUE_1)
+        ));
+
+        for bytes in [&b""[..], b"\xff\xfe", b"null", b"[]", b"{\"previous\": 1}"] {
+            assert!(!is_valid_message_value(bytes));
+            assert!(!is_valid_message_value_hash_chain(
+                MESSAGE_VALUE_2,
+                Some(bytes)
+            ));
+        }
+    }
+
+    #[test]
+    fn it_detects_a_sequence_of_zero_out_of_order() {
+        let with_previous = MESSAGE_VALUE_2.replace("\"sequence\": 2", "\"sequence\": 0");
+        let without_previous = MESSAGE_VALUE_1.replace("\"sequence\": 1", "\"sequence\": 0");
+        for message in [with_previous, without_previous] {
+            match validate_ooo_message_value_hash_chain::<_, &[u8]>(&message, None) {
+                Err(Error::SequenceWasZero { .. }) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    #[test]
+    fn it_lints_and_reports_a_sequence_of_zero() {
+        let message = MESSAGE_VALUE_2.replace("\"sequence\": 2", "\"sequence\": 0");
+        let errors = lint_message_value(&message, Some(MESSAGE_VALUE_1));
+        assert!(errors
+            .iter()
+            .any(|err| matches!(err, Error::SequenceWasZero { .. })));
+
+        let report = debug_report(&message, Some(MESSAGE_VALUE_1));
+        assert_eq!(report.sequence, Some(false));
+    }
+
+    #[test]
+    fn it_lints_and_reports_inconsistent_first_message_markers() {
+        let first_with_previous = MESSAGE_VALUE_1.replace(
+            "\"previous\": null",
+            "\"previous\": \"%/v5mCnV/kmnVtnF3zXtD4tbzoEQo4kRq/0d/bgxP1WI=.sha256\"",
+        );
+        let errors = lint_message_value::<_, &[u8]>(&first_with_previous, None);
+        assert!(errors
+            .iter()
+            .any(|err| matches!(err, Error::InconsistentFirstMessageMarkers { .. })));
+
+        let report = debug_report::<_, &[u8]>(&first_with_previous, None);
+        assert_eq!(report.first_message_markers, Some(false));
+    }
+
+    #[test]
+    fn it_detects_a_negative_timestamp() {
+        let message = MESSAGE_VALUE_1.replace("1470186877575", "-1470186877575");
+        match validate_message_value(message.as_bytes()) {
+            Err(Error::InvalidTimestamp { message: _ }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_reports_unexpected_fields_when_lenient() {
+        let message =
+            MESSAGE_VALUE_1.replace("\"hash\"", "\"extra\": true,\n  \"other\": [],\n  \"hash\"");
+        assert!(validate_message_value(&message).is_err());
+        let (message_value, unexpected) = validate_message_value_lenient(&message).unwrap();
+        assert_eq!(message_value.sequence, 1);
+        assert_eq!(unexpected, vec!["extra".to_string(), "other".to_string()]);
+
+        let (_, unexpected) = validate_message_value_lenient(MESSAGE_VALUE_1).unwrap();
+        assert!(unexpected.is_empty());
+
+        // Other checks still apply.
+        let message = message.replace("\"sha256\"", "\"sha512\"");
+        match validate_message_value_lenient(&message) {
+            Err(Error::InvalidHashFunction { .. }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_detects_an_empty_author_or_signature() {
+        let message = MESSAGE_VALUE_1.replace(
+            "\"author\": \"@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519\"",
+            "\"author\": \"\"",
+        );
+        match validate_ooo_message_value_hash_chain::<_, &[u8]>(message.as_bytes(), None) {
+            Err(Error::InvalidAuthorFormat { author }) => assert!(author.is_empty()),
+            _ => panic!(),
+        }
+
+        let signature = from_slice::<SsbMessageValue>(MESSAGE_VALUE_1.as_bytes())
+            .unwrap()
+            .signature;
+        let message = MESSAGE_VALUE_1.replace(&signature, "");
+        match validate_message_value(message.as_bytes()) {
+            Err(Error::InvalidSignatureFormat { message: _ }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_detects_a_truncated_author_or_signature() {
+        let message = MESSAGE_VALUE_1.replace(
+            "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+            "@AAAA.ed25519",
+        );
+        match validate_ooo_message_value_hash_chain::<_, &[u8]>(message.as_bytes(), None) {
+            Err(Error::InvalidAuthorFormat { author }) => assert_eq!(author, "@AAAA.ed25519"),
+            _ => panic!(),
+        }
+
+        let signature = from_slice::<SsbMessageValue>(MESSAGE_VALUE_1.as_bytes())
+            .unwrap()
+            .signature;
+        let message = MESSAGE_VALUE_1.replace(&signature, "AAAA.sig.ed25519");
+        match validate_message_value(message.as_bytes()) {
+            Err(Error::InvalidSignatureFormat { message: _ }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_lints_and_reports_a_truncated_author_or_signature() {
+        let signature = from_slice::<SsbMessageValue>(MESSAGE_VALUE_1.as_bytes())
+            .unwrap()
+            .signature;
+        let truncated = [
+            MESSAGE_VALUE_1.replace(
+                "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+                "@AAAA.ed25519",
+            ),
+            MESSAGE_VALUE_1.replace(&signature, "AAAA.sig.ed25519"),
+        ];
+        for message in truncated.iter() {
+            assert!(validate_message_value(message).is_err());
+            match &lint_message_value::<_, &[u8]>(message, None)[..] {
+                [Error::InvalidAuthorFormat { .. }] | [Error::InvalidSignatureFormat { .. }] => {}
+                other => panic!("unexpected errors: {:?}", other),
+            }
+            assert_eq!(debug_report::<_, &[u8]>(message, None).format, Some(false));
+        }
+    }
+
+    #[test]
+    fn it_detects_a_previous_reference_to_a_blob() {
+        let message = MESSAGE_VALUE_2.replace("\"previous\": \"%", "\"previous\": \"&");
+        match validate_message_value(message.as_bytes()) {
+            Err(Error::InvalidPreviousReference { message: _ }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_detects_a_message_value_longer_than_the_configured_limit() {
+        let options = ValidationOptions {
+            max_value_length: 256,
+            ..Default::default()
+        };
+        let result = validate_message_value_hash_chain_with_options::<_, &[u8]>(
+            MESSAGE_VALUE_1.as_bytes(),
+            None,
+            &options,
+        );
+        match result {
+            Err(Error::InvalidMessageValueLength {
+                message: _,
+                actual,
+                limit: 256,
+            }) => assert!(actual > 256),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_limits_the_length_by_content_type_when_configured() {
+        let options = ValidationOptions {
+            per_type_max_length: vec![("about".to_string(), 256)].into_iter().collect(),
+            ..Default::default()
+        };
+        match validate_message_value_with_options(MESSAGE_VALUE_1, &options) {
+            Err(Error::InvalidMessageValueLength { limit: 256, .. }) => {}
+            _ => panic!(),
+        }
+        // Other types are limited to `max_value_length`.
+        assert!(validate_message_value_with_options(MESSAGE_VALUE_3, &options).is_ok());
+
+        let options = ValidationOptions {
+            max_value_length: 256,
+            per_type_max_length: vec![("about".to_string(), 8192)].into_iter().collect(),
+            ..Default::default()
+        };
+        assert!(validate_message_value_with_options(MESSAGE_VALUE_1, &options).is_ok());
+        assert!(validate_message_value_with_options(MESSAGE_VALUE_3, &options).is_err());
+    }
+
+    #[test]
+    fn it_skips_the_length_check_when_configured() {
+        let options = ValidationOptions {
+            max_value_length: 256,
+            skip_length_check: true,
+            ..Default::default()
+        };
+        assert!(validate_message_value_with_options(MESSAGE_VALUE_1, &options).is_ok());
+    }
+
+    #[test]
+    fn it_detects_an_invalid_mention_link_when_enabled() {
+        let options = ValidationOptions {
+            validate_links: true,
+            ..Default::default()
+        };
+        let message = MESSAGE_VALUE_1.replace(
+            "\"name\": \"Piet\"",
+            "\"mentions\": [{\"link\": \"&MxwsfZoq7X6oqnEX/TWIlAqd6S+jsUA6T1hqZYdl7RM=.sha256\"}, {\"link\": \"&notahash\"}]",
+        );
+        let result = validate_message_value_hash_chain_with_options::<_, &[u8]>(
+            message.as_bytes(),
+            None,
+            &options,
+        );
+        match result {
+            Err(Error::InvalidLink { message: _, link }) => assert_eq!(link, "&notahash"),
+            _ => panic!(),
+        }
+        // Content without `mentions` is not affected.
+        assert!(validate_message_value_hash_chain_with_options::<_, &[u8]>(
+            MESSAGE_VALUE_1.as_bytes(),
+            None,
+            &options
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn it_detects_an_invalid_about_link_when_enabled() {
+        let options = ValidationOptions {
+            validate_about_links: true,
+            ..Default::default()
+        };
+        assert!(validate_message_value_hash_chain_with_options::<_, &[u8]>(
+            MESSAGE_VALUE_1.as_bytes(),
+            None,
+            &options
+        )
+        .is_ok());
+
+        let message = MESSAGE_VALUE_1.replace(
+            "\"about\": \"@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519\"",
+            "\"about\": \"&MxwsfZoq7X6oqnEX/TWIlAqd6S+jsUA6T1hqZYdl7RM=.sha256\"",
+        );
+        let result = validate_message_value_hash_chain_with_options::<_, &[u8]>(
+            message.as_bytes(),
+            None,
+            &options,
+        );
+        match result {
+            Err(Error::InvalidLink { message: _, link }) => {
+                assert_eq!(link, "&MxwsfZoq7X6oqnEX/TWIlAqd6S+jsUA6T1hqZYdl7RM=.sha256")
+            }
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_detects_a_media_link_which_is_not_a_blob_when_enabled() {
+        let options = ValidationOptions {
+            validate_blob_links: true,
+            ..Default::default()
+        };
+        assert!(validate_message_value_with_options(MESSAGE_VALUE_2, &options).is_ok());
+
+        let message_link = "%/v5mCnV/kmnVtnF3zXtD4tbzoEQo4kRq/0d/bgxP1WI=.sha256";
+        let message = MESSAGE_VALUE_2.replace(
+            "\"link\": \"&MxwsfZoq7X6oqnEX/TWIlAqd6S+jsUA6T1hqZYdl7RM=.sha256\"",
+            &format!("\"link\": \"{}\"", message_link),
+        );
+        assert!(validate_message_value(&message).is_ok());
+        match validate_message_value_with_options(&message, &options) {
+            Err(Error::InvalidLink { link, .. }) => assert_eq!(link, message_link),
+            _ => panic!(),
+        }
+
+        // Mentions of messages and feeds are not links to media.
+        let message = MESSAGE_VALUE_1.replace(
+            "\"name\": \"Piet\"",
+            &format!(
+                "\"mentions\": [{{\"link\": \"{}\"}}, {{\"link\": \"{}\", \"type\": \"image/png\"}}]",
+                message_link, message_link
+            ),
+        );
+        match validate_message_value_with_options(&message, &options) {
+            Err(Error::InvalidLink { link, .. }) => assert_eq!(link, message_link),
+            _ => panic!(),
+        }
+        let message = MESSAGE_VALUE_1.replace(
+            "\"name\": \"Piet\"",
+            &format!("\"mentions\": [{{\"link\": \"{}\"}}]", message_link),
+        );
+        assert!(validate_message_value_with_options(&message, &options).is_ok());
+    }
+
+    #[test]
+    fn it_detects_a_fractional_timestamp_when_enabled() {
+        let message = MESSAGE_VALUE_1.replace("1470186877575", "1470186877575.5");
+        assert!(validate_message_value(&message).is_ok());
+
+        let options = ValidationOptions {
+            require_integer_timestamp: true,
+            ..Default::default()
+        };
+        assert!(validate_message_value_with_options(MESSAGE_VALUE_1, &options).is_ok());
+        match validate_message_value_with_options(&message, &options) {
+            Err(Error::InvalidTimestamp { message: _ }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_converts_valid_message_values() {
+        let message_value = SsbMessageValue::try_from(MESSAGE_VALUE_1).unwrap();
+        assert_eq!(message_value.sequence, 1);
+        assert!(SsbMessageValue::try_from(MESSAGE_VALUE_2.as_bytes()).is_ok());
+
+        let message = MESSAGE_VALUE_1.replace("sha256", "sha512");
+        match SsbMessageValue::try_from(message.as_str()) {
+            Err(Error::InvalidHashFunction { message: _ }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_warns_about_discouraged_constructs() {
+        assert!(validate_message_value_with_warnings(MESSAGE_VALUE_1)
+            .unwrap()
+            .is_empty());
+
+        let message = MESSAGE_VALUE_1
+            .replace(
+                "  \"author\": \"@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519\",\n  \"sequence\": 1,",
+                "  \"sequence\": 1,\n  \"author\": \"@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519\",",
+            )
+            .replace("1470186877575", "1470186877575.5");
+        assert_eq!(
+            validate_message_value_with_warnings(message).unwrap(),
+            vec![
+                Warning::FractionalTimestamp {
+                    timestamp: 1470186877575.5
+                },
+                Warning::SequenceBeforeAuthor
+            ]
+        );
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn it_validates_message_values_decoded_with_serde_json() {
+        let value_1: serde_json::Value = serde_json::from_str(MESSAGE_VALUE_1).unwrap();
+        let value_2: serde_json::Value = serde_json::from_str(MESSAGE_VALUE_2).unwrap();
+        assert!(validate_message_value_from_json(&value_1, None).is_ok());
+        assert!(validate_message_value_from_json(&value_2, Some(&value_1)).is_ok());
+
+        match validate_message_value_from_json(&value_2, None) {
+            Err(Error::FirstMessageDidNotHaveSequenceOfOne { .. }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_validates_against_a_previous_summary() {
+        let previous: Previous<&[u8]> = Previous::Full(MESSAGE_VALUE_1.as_bytes());
+        assert!(
+            validate_message_value_hash_chain_with_previous(MESSAGE_VALUE_2, Some(previous))
+                .is_ok()
+        );
+
+        let author = "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519".to_string();
+        let summary = |key: &str, sequence| -> Previous<&[u8]> {
+            Previous::Summary {
+                key: Multihash::from_legacy(key.as_bytes()).unwrap().0,
+                sequence,
+                author: author.clone(),
+            }
+        };
+        let previous_key = "%/v5mCnV/kmnVtnF3zXtD4tbzoEQo4kRq/0d/bgxP1WI=.sha256";
+        assert!(validate_message_value_hash_chain_with_previous(
+            MESSAGE_VALUE_2,
+            Some(summary(previous_key, 1))
+        )
+        .is_ok());
+
+        match validate_message_value_hash_chain_with_previous(
+            MESSAGE_VALUE_2,
+            Some(summary(previous_key, 2)),
+        ) {
+            Err(Error::InvalidSequenceNumber {
+                message: _,
+                actual: 2,
+                expected: 3,
+            }) => {}
+            _ => panic!(),
+        }
+
+        match validate_message_value_hash_chain_with_previous(
+            MESSAGE_VALUE_2,
+            Some(summary(previous_key, u64::MAX)),
+        ) {
+            Err(Error::SequenceOverflow { message: _ }) => {}
+            _ => panic!(),
+        }
+
+        let other_key = "%kLWDux4wCG+OdQWAHnpBGzGlCehqMLfgLbzlKCvgesU=.sha256";
+        match validate_message_value_hash_chain_with_previous(
+            MESSAGE_VALUE_2,
+            Some(summary(other_key, 1)),
+        ) {
+            Err(Error::ForkedFeed { previous_seq: 1 }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_accepts_a_misplaced_signature_when_lenient() {
+        // Move the `signature` to the front.
+        let (fields, signature) =
+            MESSAGE_VALUE_1.split_at(MESSAGE_VALUE_1.find(",\n  \"signature\"").unwrap());
+        let message = format!(
+            "{{\n{},\n{}\n}}",
+            &signature[2..signature.len() - 2],
+            &fields[2..]
+        );
+        let options = ValidationOptions {
+            lenient_order: true,
+            ..Default::default()
+        };
+
+        match validate_message_value(message.as_bytes()) {
+            Err(Error::InvalidMessageValueOrder { .. }) => {}
+            _ => panic!(),
+        }
+        assert!(validate_message_value_with_options(message.as_bytes(), &options).is_ok());
+    }
+
+    #[test]
+    fn it_rejects_other_field_orders_when_lenient() {
+        let message = MESSAGE_VALUE_1.replace(
+            "  \"timestamp\": 1470186877575,\n  \"hash\": \"sha256\",",
+            "  \"hash\": \"sha256\",\n  \"timestamp\": 1470186877575,",
+        );
+        let options = ValidationOptions {
+            lenient_order: true,
+            ..Default::default()
+        };
+
+        match validate_message_value_with_options(message.as_bytes(), &options) {
+            Err(Error::InvalidMessageValueOrder { .. }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_detects_a_content_type_of_invalid_utf8_length_when_enabled() {
+        // 20 UTF-16 code units, which decodes, but 60 UTF-8 bytes.
+        let long_type = "\u{20ac}".repeat(20);
+        let message = MESSAGE_VALUE_1.replace(
+            "\"type\": \"about\"",
+            &format!("\"type\": \"{}\"", long_type),
+        );
+        assert!(validate_message_value(&message).is_ok());
+
+        let options = ValidationOptions {
+            validate_content_type_length: true,
+            ..Default::default()
+        };
+        match validate_message_value_with_options(&message, &options) {
+            Err(Error::InvalidContentType {
+                message: _,
+                content_type,
+            }) => assert_eq!(content_type, long_type),
+            _ => panic!(),
+        }
+        assert!(validate_message_value_with_options(MESSAGE_VALUE_1, &options).is_ok());
+    }
+
+    #[test]
+    fn it_rejects_a_content_type_of_invalid_length() {
+        // `ContentValue` requires the `type` of object content to be 3 to 52 UTF-16 code units
+        // long.
+        let long_type = "a".repeat(53);
+        for content_type in ["ab", "abc", long_type.as_str(), &long_type[1..]] {
+            let message = MESSAGE_VALUE_1.replace(
+                "\"type\": \"about\"",
+                &format!("\"type\": \"{}\"", content_type),
+            );
+            let result = validate_message_value(&message);
+            if (3..=52).contains(&content_type.len()) {
+                assert!(result.is_ok());
+            } else {
+                match result {
+                    Err(Error::InvalidMessage { .. }) => {}
+                    _ => panic!(),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn it_rejects_non_finite_numbers_in_content() {
+        // Numbers in `content` are decoded as `LegacyF64`, which is always finite.
+        for number in ["NaN", "Infinity", "-Infinity", "1e400"] {
+            let message = MESSAGE_VALUE_2.replace("642763", number);
+            match validate_message_value(&message) {
+                Err(Error::InvalidMessage { .. }) => {}
+                _ => panic!("{} was accepted", number),
+            }
+        }
+    }
+
+    #[test]
+    fn it_rejects_content_which_is_not_an_object_or_string() {
+        // `ContentValue` only decodes objects and strings, so other content fails to parse.
+        for content in [
+            Value::Array(vec![Value::String("post".to_string())]),
+            Value::Null,
+        ] {
+            let mut value = from_slice::<SsbMessageValue>(MESSAGE_VALUE_1.as_bytes()).unwrap();
+            value.content = ContentValue(content);
+            let message = to_vec(&value, false).unwrap();
+            match validate_message_value(&message) {
+                Err(Error::InvalidMessage { .. }) => {}
+                _ => panic!(),
+            }
+        }
+    }
+
+    #[test]
+    fn it_detects_invalid_contact_content_when_enabled() {
+        let options = ValidationOptions {
+            validate_contact_content: true,
+            ..Default::default()
+        };
+        assert!(validate_message_value_with_options(MESSAGE_VALUE_3, &options).is_ok());
+        // Other message types are not affected.
+        assert!(validate_message_value_with_options(MESSAGE_VALUE_1, &options).is_ok());
+
+        let message = MESSAGE_VALUE_3.replace("\"following\": true", "\"following\": \"yes\"");
+        assert!(validate_message_value(&message).is_ok());
+        match validate_message_value_with_options(&message, &options) {
+            Err(Error::InvalidContent {
+                message: _,
+                reason: "contact following must be a boolean",
+            }) => {}
+            _ => panic!(),
+        }
+
+        let message = MESSAGE_VALUE_3.replace("\"contact\": \"@", "\"contact\": \"%");
+        match validate_message_value_with_options(&message, &options) {
+            Err(Error::InvalidContent {
+                message: _,
+                reason: "contact must be a feed reference",
+            }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_detects_invalid_vote_content_when_enabled() {
+        let options = ValidationOptions {
+            validate_vote_content: true,
+            ..Default::default()
+        };
+        let vote_value = |message: &str| {
+            let value = from_slice::<SsbMessage>(message.as_bytes()).unwrap().value;
+            to_vec(&value, false).unwrap()
+        };
+
+        let message = vote_value(MESSAGE_PRIVATE_PREV);
+        assert!(validate_message_value_with_options(&message, &options).is_ok());
+        // Other message types are not affected.
+        assert!(validate_message_value_with_options(MESSAGE_VALUE_1, &options).is_ok());
+
+        let message = vote_value(&MESSAGE_PRIVATE_PREV.replace("\"value\": 1,", "\"value\": 0.5,"));
+        assert!(validate_message_value(&message).is_ok());
+        match validate_message_value_with_options(&message, &options) {
+            Err(Error::InvalidContent {
+                message: _,
+                reason: "vote value must be an integer",
+            }) => {}
+            _ => panic!(),
+        }
+
+        let message = vote_value(&MESSAGE_PRIVATE_PREV.replace("\"link\": \"%", "\"link\": \"&"));
+        match validate_message_value_with_options(&message, &options) {
+            Err(Error::InvalidContent {
+                message: _,
+                reason: "vote link must be a message reference",
+            }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_detects_a_truncated_ciphertext_when_enabled() {
+        let options = ValidationOptions {
+            validate_ciphertext_length: true,
+            ..Default::default()
+        };
+        let mut value = from_slice::<SsbMessage>(MESSAGE_PRIVATE.as_bytes())
+            .unwrap()
+            .value;
+        let message = to_vec(&value, false).unwrap();
+        assert!(validate_message_value_with_options(&message, &options).is_ok());
+
+        value.content = ContentValue(Value::String("AAAA.box".to_string()));
+        let message = to_vec(&value, false).unwrap();
+        assert!(validate_message_value(&message).is_ok());
+        match validate_message_value_with_options(&message, &options) {
+            Err(Error::CiphertextTooShort {
+                message: _,
+                length: 3,
+                minimum: _,
+            }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[cfg(feature = "parallel")]
     #[test]
     fn it_validates_message_values_in_parallel() {
         let messages = [MESSAGE_VALUE_1.as_bytes(), MESSAGE_VALUE_2.as_bytes()];
@@ -530,6 +2650,7 @@ mod tests {
         .is_ok());
     }
 
+    #[cfg(feature = "parallel")]
     #[test]
     fn it_validates_ooo_message_values_in_parallel() {
         let messages = [
@@ -542,6 +2663,7 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[cfg(feature = "parallel")]
     #[test]
     fn it_validates_message_values_from_different_authors_in_parallel() {
         let messages = [
@@ -551,4 +2673,49 @@ mod tests {
         let result = par_validate_message_value(&messages[..]);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn it_validates_a_box2_private_message_value() {
+        assert!(validate_message_value(MESSAGE_VALUE_PRIVATE_BOX2.as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn it_detects_an_unknown_private_message_suffix() {
+        let message = MESSAGE_VALUE_PRIVATE_BOX2.replace(".box2", ".box3");
+        match validate_message_value(message.as_bytes()) {
+            Err(Error::InvalidBase64 { message: _ }) => {}
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_skips_the_content_checks_when_configured() {
+        let options = ValidationOptions {
+            validate_about_links: true,
+            skip_content_checks: true,
+            ..Default::default()
+        };
+        let message = MESSAGE_VALUE_PRIVATE_BOX2.replace(".box2", ".box3");
+        assert!(validate_message_value_with_options(message.as_bytes(), &options).is_ok());
+        let message = MESSAGE_VALUE_1.replace(
+            "\"about\": \"@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519\"",
+            "\"about\": \"not a reference\"",
+        );
+        assert!(validate_message_value_with_options(message.as_bytes(), &options).is_ok());
+
+        // The metadata is still validated.
+        let message = MESSAGE_VALUE_1.replace("\"hash\": \"sha256\"", "\"hash\": \"sha512\"");
+        assert!(validate_message_value_with_options(message.as_bytes(), &options).is_err());
+    }
+
+    #[test]
+    fn it_identifies_an_unexpected_wrapper_field() {
+        let message = MESSAGE_VALUE_1.replace("\"hash\"", "\"meta\": {\"set\": true},\n  \"hash\"");
+        match validate_message_value(message.as_bytes()) {
+            Err(Error::UnexpectedMessageValueField { message: _, field }) => {
+                assert_eq!(field, "meta")
+            }
+            _ => panic!(),
+        }
+    }
 }
@@ -0,0 +1,148 @@
+//! Options for enabling validation checks which go beyond the feed specification.
+//!
+//! The default options perform exactly the checks described in the [crate documentation](crate).
+//! Functions which accept options are suffixed with `_with_options`.
+use std::collections::HashMap;
+
+use crate::utils::MAX_MESSAGE_VALUE_UTF16_UNITS;
+
+/// Optional validation checks. Construct with [`Default`] and enable the checks you need:
+///
+///```
+///use ssb_validate::options::ValidationOptions;
+///let options = ValidationOptions {
+///    enforce_monotonic_timestamps: true,
+///    ..Default::default()
+///};
+///```
+#[derive(Debug, Clone)]
+pub struct ValidationOptions {
+    /// Reject a message whose `timestamp` is less than the `timestamp` of the previous message.
+    ///
+    /// Timestamps are asserted by the author and not required to increase by the spec, but a
+    /// decrease within a feed can indicate a tampered or replayed feed. Only applies when a
+    /// previous message is being checked.
+    pub enforce_monotonic_timestamps: bool,
+
+    /// Accept the field orders of a message value which the JavaScript implementation tolerated,
+    /// rather than rejecting them with `Error::InvalidMessageValueOrder` (see
+    /// [`is_legacy_js_order`](crate::utils::is_legacy_js_order)).
+    ///
+    /// The JavaScript [ssb-keys](https://github.com/ssb-js/ssb-keys) `verifyObj` removes the
+    /// `signature` before verifying a message value, so a `signature` which is not the last field
+    /// was accepted. This allows legacy feeds with such messages to be replicated. The other fields
+    /// must still be in the expected order.
+    pub lenient_order: bool,
+
+    /// Reject a message whose `content` is nested more than this many levels deep (see
+    /// [`is_deeper_than`](crate::utils::is_deeper_than)).
+    ///
+    /// Deeply nested content can exhaust the stack of downstream consumers which process it
+    /// recursively. A limit of 32 is sufficient for all common message types.
+    pub max_content_depth: Option<usize>,
+
+    /// Reject a message whose `content.mentions` includes a `link` which is not a valid feed,
+    /// message or blob reference (see [`is_valid_link`](crate::utils::is_valid_link)).
+    ///
+    /// Content without `mentions` is not affected.
+    pub validate_links: bool,
+    /// Reject an `about` message whose `about` is not a feed or message reference.
+    ///
+    /// Only the `about` field is checked; the optional fields of an `about` message (such as
+    /// `name`, `image` and `description`) and the content of other message types are not affected.
+    pub validate_about_links: bool,
+    /// Reject a message whose `content` links to media with something other than a blob
+    /// reference (`&...sha256`), with `Error::InvalidLink`.
+    ///
+    /// The media links checked are the `content.image` (either a reference or an object with a
+    /// `link`), and the `link` of each of the `content.mentions` which has a MIME `type` (such as
+    /// `image/png`).
+    pub validate_blob_links: bool,
+    /// Reject a message whose object `content` has a `type` which is not between 3 and 52 UTF-8
+    /// bytes long, with `Error::InvalidContentType`.
+    ///
+    /// Decoding already requires the `type` to be between 3 and 52 UTF-16 code units long, so
+    /// this only affects a `type` with non-ASCII characters (which take more UTF-8 bytes than
+    /// UTF-16 code units). Content without a `type` is not affected.
+    pub validate_content_type_length: bool,
+    /// Reject a `vote` message whose `vote.link` is not a message reference or whose `vote.value`
+    /// is not an integer, with `Error::InvalidContent`.
+    ///
+    /// The content of other message types is not affected.
+    pub validate_vote_content: bool,
+    /// Reject a `contact` message whose `contact` is not a feed reference or whose `following` or
+    /// `blocking` (when present) is not a boolean, with `Error::InvalidContent`.
+    ///
+    /// The content of other message types is not affected.
+    pub validate_contact_content: bool,
+    /// Reject a `.box` private message whose decoded ciphertext is shorter than the smallest
+    /// possible [private-box](https://ssbc.github.io/scuttlebutt-protocol-guide/#private-messages)
+    /// (a nonce, an ephemeral key, a single recipient header and an empty body).
+    ///
+    /// Canonical base64 alone does not guarantee this, so a truncated ciphertext would otherwise
+    /// be accepted. `.box2` content is not affected.
+    pub validate_ciphertext_length: bool,
+    /// The maximum length of a message value, in UTF-16 code units, when serialized as JSON (see
+    /// [`message_value_length`](crate::utils::message_value_length)).
+    ///
+    /// Defaults to [`MAX_MESSAGE_VALUE_UTF16_UNITS`], the limit of the feed specification.
+    /// Networks with different rules can raise or lower it.
+    pub max_value_length: usize,
+    /// The maximum length of a message value, as for
+    /// [`max_value_length`](ValidationOptions::max_value_length), by the `type` of its content.
+    ///
+    /// A message value whose content is an object with a `type` in this map is limited to the
+    /// given length instead of `max_value_length`. Other message values (including private
+    /// messages) are limited to `max_value_length`. Empty by default.
+    pub per_type_max_length: HashMap<String, usize>,
+    /// Skip every check of the message `content`: the encoding of private message content, and
+    /// the content checks enabled by the other options (such as
+    /// [`validate_links`](ValidationOptions::validate_links) and
+    /// [`max_content_depth`](ValidationOptions::max_content_depth)).
+    ///
+    /// This suits metadata-only validation, such as of private feeds whose content cannot be read.
+    /// The order, hash, length, sequence and continuity of the message values are still validated,
+    /// and the content must still be an object or a string to be decoded.
+    pub skip_content_checks: bool,
+    /// Skip the length check of the message value (see
+    /// [`max_value_length`](ValidationOptions::max_value_length)).
+    ///
+    /// Serializing the message value to measure its length is the most expensive check. Only
+    /// skip it for trusted feeds whose message sizes are already limited elsewhere.
+    pub skip_length_check: bool,
+    /// Reject a message value whose `timestamp` has a fractional part, with
+    /// `Error::InvalidTimestamp`.
+    ///
+    /// Fractional timestamps are legal, but some tooling treats them as invalid. Only the
+    /// `timestamp` of the message value is checked, not the receive timestamp of a `KVT` message.
+    pub require_integer_timestamp: bool,
+    /// Reject a `KVT` message whose receive `timestamp` (the `timestamp` outside of the `value`)
+    /// is negative, with `Error::InvalidReceiveTimestamp`.
+    ///
+    /// The receive timestamp is assigned by the receiving peer rather than the author, and is
+    /// optional; a message without one is not affected.
+    pub validate_kvt_timestamp: bool,
+}
+
+impl Default for ValidationOptions {
+    fn default() -> Self {
+        ValidationOptions {
+            enforce_monotonic_timestamps: false,
+            lenient_order: false,
+            max_content_depth: None,
+            validate_links: false,
+            validate_about_links: false,
+            validate_blob_links: false,
+            validate_content_type_length: false,
+            validate_vote_content: false,
+            validate_contact_content: false,
+            validate_ciphertext_length: false,
+            max_value_length: MAX_MESSAGE_VALUE_UTF16_UNITS,
+            per_type_max_length: HashMap::new(),
+            skip_content_checks: false,
+            skip_length_check: false,
+            require_integer_timestamp: false,
+            validate_kvt_timestamp: false,
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! Incremental validation of a feed of message values delivered in arbitrary chunks of bytes.
+//!
+//! This suits network protocols with their own framing, where a chunk may hold part of a message,
+//! or several messages. The messages are expected to be JSON encoded message values, one after
+//! another (optionally separated by whitespace).
+use ssb_multiformats::multihash::Multihash;
+
+use crate::error::Result;
+use crate::message_value::{validate_message_value_against_previous, SsbMessageValue};
+use crate::options::ValidationOptions;
+use crate::utils;
+
+/// Push-based validator of a feed of message values, starting from the first message of the feed.
+///
+/// Bytes are supplied with [`feed`](StreamValidator::feed) as they arrive. Each complete message
+/// value is validated in relation to the one before it, and the keys of the valid messages are
+/// returned; an incomplete message is buffered until the rest of it arrives. Once a message fails
+/// validation the error is returned and no further messages are accepted, since they can no longer
+/// be validated against a known-good previous message.
+///
+/// # Example
+///```
+///use ssb_validate::stream::StreamValidator;
+///use ssb_validate::test_data::{MESSAGE_VALUE_1, MESSAGE_VALUE_2};
+///
+///let bytes = [MESSAGE_VALUE_1, MESSAGE_VALUE_2].concat();
+///let (first_chunk, second_chunk) = bytes.as_bytes().split_at(100);
+///
+///let mut validator = StreamValidator::new();
+///assert!(validator.feed(first_chunk).unwrap().is_empty());
+///assert_eq!(validator.feed(second_chunk).unwrap().len(), 2);
+///```
+#[derive(Debug, Default)]
+pub struct StreamValidator {
+    buffer: Vec<u8>,
+    previous: Option<(SsbMessageValue, Multihash)>,
+    failed: bool,
+}
+
+impl StreamValidator {
+    /// Create a validator for a feed, starting from the first message of the feed.
+    pub fn new() -> StreamValidator {
+        StreamValidator::default()
+    }
+
+    /// Add a chunk of bytes, returning the keys of the messages it completes, in feed order.
+    ///
+    /// Returns the error of the first invalid message, if any. The keys of messages validated
+    /// before it in the same chunk are not returned, but are reflected by
+    /// [`tip`](StreamValidator::tip). After an error, all further chunks are ignored.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<Vec<Multihash>> {
+        if self.failed {
+            return Ok(Vec::new());
+        }
+        self.buffer.extend_from_slice(chunk);
+
+        let mut keys = Vec::new();
+        loop {
+            let start = self
+                .buffer
+                .iter()
+                .position(|byte| !byte.is_ascii_whitespace())
+                .unwrap_or(self.buffer.len());
+            self.buffer.drain(..start);
+
+            // Anything other than an object is not a message value, so validating it reports the
+            // decoding error.
+            let end = match self.buffer.first() {
+                None => break,
+                Some(b'{') => match object_end(&self.buffer) {
+                    Some(end) => end,
+                    None => break,
+                },
+                Some(_) => self.buffer.len(),
+            };
+
+            let message_bytes: Vec<u8> = self.buffer.drain(..end).collect();
+            let previous = self
+                .previous
+                .as_ref()
+                .map(|(previous_value, previous_key)| (previous_value, previous_key));
+            match validate_message_value_against_previous(
+                &message_bytes,
+                previous,
+                &ValidationOptions::default(),
+            ) {
+                Ok(message_value) => {
+                    let key = utils::multihash_from_bytes(&message_bytes);
+                    keys.push(key.clone());
+                    self.previous = Some((message_value, key));
+                }
+                Err(err) => {
+                    self.failed = true;
+                    self.buffer.clear();
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(keys)
+    }
+
+    /// The key of the last valid message, if any. This is the `previous` of the next message.
+    pub fn tip(&self) -> Option<&Multihash> {
+        self.previous.as_ref().map(|(_, key)| key)
+    }
+
+    /// The number of bytes of an incomplete message which are waiting for the rest of it.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+}
+
+/// The length of the JSON object at the start of the bytes, or `None` if it is incomplete.
+fn object_end(bytes: &[u8]) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (idx, byte) in bytes.iter().enumerate() {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    return Some(idx + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::stream::StreamValidator;
+    use crate::test_data::{MESSAGE_VALUE_1, MESSAGE_VALUE_2, MESSAGE_VALUE_3};
+    use crate::utils::multihash_from_bytes;
+
+    #[test]
+    fn it_validates_messages_split_across_chunks() {
+        let bytes = [
+            MESSAGE_VALUE_1,
+            "\n",
+            MESSAGE_VALUE_2,
+            "\n",
+            MESSAGE_VALUE_3,
+        ]
+        .concat();
+
+        let mut validator = StreamValidator::new();
+        let keys: Vec<_> = bytes
+            .as_bytes()
+            .chunks(7)
+            .flat_map(|chunk| validator.feed(chunk).unwrap())
+            .collect();
+
+        assert_eq!(
+            keys,
+            vec![
+                multihash_from_bytes(MESSAGE_VALUE_1.as_bytes()),
+                multihash_from_bytes(MESSAGE_VALUE_2.as_bytes()),
+                multihash_from_bytes(MESSAGE_VALUE_3.as_bytes()),
+            ]
+        );
+        assert_eq!(validator.tip(), keys.last());
+        assert_eq!(validator.buffered(), 0);
+    }
+
+    #[test]
+    fn it_validates_several_messages_in_one_chunk() {
+        let bytes = [MESSAGE_VALUE_1, MESSAGE_VALUE_2, &MESSAGE_VALUE_3[..10]].concat();
+
+        let mut validator = StreamValidator::new();
+        assert_eq!(validator.feed(bytes.as_bytes()).unwrap().len(), 2);
+        assert_eq!(validator.buffered(), 10);
+        assert_eq!(
+            validator.feed(&MESSAGE_VALUE_3.as_bytes()[10..]).unwrap(),
+            vec![multihash_from_bytes(MESSAGE_VALUE_3.as_bytes())]
+        );
+    }
+
+    #[test]
+    fn it_stops_after_the_first_invalid_message() {
+        let bytes = [MESSAGE_VALUE_1, MESSAGE_VALUE_3, MESSAGE_VALUE_2].concat();
+
+        let mut validator = StreamValidator::new();
+        match validator.feed(bytes.as_bytes()) {
+            Err(Error::InvalidSequenceNumber { .. }) => {}
+            _ => panic!(),
+        }
+        assert_eq!(
+            validator.tip(),
+            Some(&multihash_from_bytes(MESSAGE_VALUE_1.as_bytes()))
+        );
+        assert!(validator
+            .feed(MESSAGE_VALUE_2.as_bytes())
+            .unwrap()
+            .is_empty());
+
+        let mut validator = StreamValidator::new();
+        match validator.feed(b"[]") {
+            Err(Error::InvalidMessage { .. }) => {}
+            _ => panic!(),
+        }
+    }
+}
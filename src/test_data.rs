@@ -314,6 +314,30 @@ pub const MESSAGE_2_FORK: &str = r##"{
   "timestamp": 1571140551485
 }"##;
 
+pub const MESSAGE_2_SIBLING: &str = r##"{
+  "key": "%sFwyw1toGNVEp2czitisBiQkIM5L4GbSz65LaRDn+sM=.sha256",
+  "value": {
+    "previous": "%/v5mCnV/kmnVtnF3zXtD4tbzoEQo4kRq/0d/bgxP1WI=.sha256",
+    "author": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+    "sequence": 2,
+    "timestamp": 1470187292813,
+    "hash": "sha256",
+    "content": {
+      "type": "about",
+      "about": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+      "image": {
+        "link": "&MxwsfZoq7X6oqnEX/TWIlAqd6S+jsUA6T1hqZYdl7RM=.sha256",
+        "size": 642763,
+        "type": "image/png",
+        "width": 512,
+        "height": 512
+      }
+    },
+    "signature": "j3C7Us3JDnSUseF4ycRB0dTMs0xC6NAriAFtJWvx2uyz0K4zSj6XL8YA4BVqv+AHgo08+HxXGrpJlZ3ADwNnDw==.sig.ed25519"
+  },
+  "timestamp": 1571140551485
+}"##;
+
 pub const MESSAGE_WITH_UNICODE: &str = r##"{
   "key": "%lYAK7Lfigw00zMt/UtVg5Ol9XdR4BHWUCxq4r2Ops90=.sha256",
   "value": {
@@ -449,3 +473,21 @@ pub const MESSAGE_PRIVATE_INVALID: &str = r##"{
     },
   "timestamp": 1620198134771
 }"##;
+
+pub const MESSAGE_VALUE_PRIVATE_BOX2: &str = r##"{
+  "previous": "%Z694dkKDUmNtoSwwjLG9cl7j0Dd26EDp0DRDmyPl1Lc=.sha256",
+  "sequence": 24148,
+  "author": "@iL6NzQoOLFP18pCpprkbY80DMtiG4JFFtVSVUaoGsOQ=.ed25519",
+  "timestamp": 1620171292121,
+  "hash": "sha256",
+  "content": "siZEm1zFx1icq0SrEynGDpNRmJCXMxTB3iEteXFn+IhJH8WhMbT8tp9qOIaFkIYcdOyerSon6RK0l4RE1ZdDh/3lcGZSdP0Ljq59qsdqlf2ngwbIbV9AWdPRrPsoVZBV6RhI+YcVTloWWP5aauu1hZKjcm62ezLBTQ3EmFPYtDuwsOFkx9/7FP97ljhj67CwvlGzuiWp6FNICHbt5kOCxs9H0k6Tr8JJVdaJtJ2pqkX4p0ECMuEuYxCYbh3FpncCqlNZJXb0dj3iSsfsMNWTJLDqfkqJKH1jBVfxDL6+xAXBDS+E4F2hD4y9gRDZEej99uVBQWlbxr5eCRV+VbfBGYxwoAYtqux6rg3jBabImKKinBwHShEP5F/+wlb9IxQn4swyOgyv+UKx/jbx+91Ayso5bnNPZMpwRRX5p5DbpK1BnryeVJhktMgFqgni1g0lHyU8sQ2QzwZgXGw7dfYoamkqK4D24NOLnUoHuVuhd7Q5SxZWSAO6wpDa4nrODePoJdl328pbMwCoQlUNeHINmKxh/o/oCNbgXitn4oN3kSVEg/umdgwwI94gmZUjiYwP1v7HA7dI.box2",
+  "signature": "n4Wepa4fxq+xLlmfCxwiC489rMZlnnrBFOkWMuGAv80O7GK0XZUn1zfuCP9fQBab1+P0m1g+OLiyWwqHnwdTBw==.sig.ed25519"
+}"##;
+
+pub const BENDY_BUTT_MESSAGE_1: &[u8] = b"ll34:\x00\x03cd\x85\x86\x89q\xea\xc5\xac\xa3<J\x0e\x18\x00\xa8\xa1\x1d\x98\x0b\xcf\x0e7v\xb3\x10\x02\xe2\xc5\xdb\x91\xb2i1e2:\x06\x02i1640000000000eld11:feedpurpose6:\x06\x00main4:type23:\x06\x00metafeed/add/existinge66:\x04\x00\x9f\xcd\xc2\xcf\xd3~\xb7\xbe\x1e\xachi]\xc9\xd5)Xc\x1e\x00\xcd\xe0\x00\xcb\x1bY\x9e\xf0PMM\xdd}\x9c5\xd8\xd1\x5c\xe2[[\x0dqctr\xdf\x8a8\xbb\xbc]\xd1SN\xeeI\x83\x97'#r\x1f\x16ee66:\x04\x00\x00s\xec&mO\xb4\xad\xbf=\x10J\xa7\x14\xf9\xf1\x102\xfd\x8a\xb6\xd8\x82\x9f\xc4\x0bR\xc8od\x85\xd7\x92\x8c\xc2\xeb\xd4do?\xe3\xf3t\xbe\x11\xd9\x05\xbfK\xe2u\xfa\x86\xf3\x88\x9d\x82\xa9\xf7\xdc^A\xdd2e";
+
+pub const BENDY_BUTT_MESSAGE_2: &[u8] = b"ll34:\x00\x03cd\x85\x86\x89q\xea\xc5\xac\xa3<J\x0e\x18\x00\xa8\xa1\x1d\x98\x0b\xcf\x0e7v\xb3\x10\x02\xe2\xc5\xdb\x91\xb2i2e34:\x01\x04\xd6M\x8d\xebY\xa5\xd0q\x22\xfej+\xfb\xb0\x15S(GQ\x07Rt\xf4\xa5\x95\x89F\x98c[H~i1640000001000eld11:feedpurpose6:\x06\x00main4:type22:\x06\x00metafeed/add/derivede66:\x04\x00\x9f\xcd\xc2\xcf\xd3~\xb7\xbe\x1e\xachi]\xc9\xd5)Xc\x1e\x00\xcd\xe0\x00\xcb\x1bY\x9e\xf0PMM\xdd}\x9c5\xd8\xd1\x5c\xe2[[\x0dqctr\xdf\x8a8\xbb\xbc]\xd1SN\xeeI\x83\x97'#r\x1f\x16ee66:\x04\x00\x00s\xec&mO\xb4\xad\xbf=\x10J\xa7\x14\xf9\xf1\x102\xfd\x8a\xb6\xd8\x82\x9f\xc4\x0bR\xc8od\x85\xd7\x92\x8c\xc2\xeb\xd4do?\xe3\xf3t\xbe\x11\xd9\x05\xbfK\xe2u\xfa\x86\xf3\x88\x9d\x82\xa9\xf7\xdc^A\xdd2e";
+
+pub const GABBY_GROVE_MESSAGE_1: &[u8] = b"\x83X\x5c\x85\xf6\xd9\x04\x1aX!\x01d\x09`\xe9\xb9\xa4#>7s|\x87_)\x92d\x5c\xacQ\x09\xf2@\xe8CQ\xc0\x10_\xbf\x19\xb7/\x01\x1b\x00\x00\x01o\xdfV\xb8\x00\x83\xd9\x04\x1aX!\x03\xb3K|Q\x13\x0a\xb4\xf8yY\xc6\xaceg \xaf\xf0)JB\x07\xfc>,\xbc\x13\xee\xac\x8d\xd3\xe0\xf8\x18\x19\x01X@\xaa\x85\x83\xd83c\x1b['>8\x88jk\x14\x1e\x84\xa2\xa8\xbcj\x8e\xaf\x92\xd8d\xc4\xfa)\x1b\xdd\x91\xe4\xd3~\x824\x95\xe0?\xf2\xdf\xffQ\x83\xe9m\x870*\x93x\x9e\xf5\xb6\xc69\xe4\xf4o\x1d,FpX\x19{\x22type\x22:\x22test\x22,\x22count\x22:1}";
+
+pub const GABBY_GROVE_MESSAGE_2: &[u8] = b"\x83X\x81\x85\xd9\x04\x1aX!\x02\x90\x88\x22\xec\xc9\xec\xbf\x98T\x17\xfb\xdc\x89<O#k\x98\xac=\x01{\xc3n\xc6\xdaT\x09\x07\xe9\xf3i\xd9\x04\x1aX!\x01d\x09`\xe9\xb9\xa4#>7s|\x87_)\x92d\x5c\xacQ\x09\xf2@\xe8CQ\xc0\x10_\xbf\x19\xb7/\x02\x1b\x00\x00\x01o\xdfV\xbb\xe8\x83\xd9\x04\x1aX!\x03Y\xbd=e\x8d\xf6\xc3B\x0ab\xe2\xef>\xf1-\xf4\x94\xc3!\xa0fm\x1d\xf9\x0fR\xf8\x07\xbf\x1dO0\x18\x19\x01X@\xaa\x85\x83\xd83c\x1b['>8\x88jk\x14\x1e\x84\xa2\xa8\xbcj\x8e\xaf\x92\xd8d\xc4\xfa)\x1b\xdd\x91\xe4\xd3~\x824\x95\xe0?\xf2\xdf\xffQ\x83\xe9m\x870*\x93x\x9e\xf5\xb6\xc69\xe4\xf4o\x1d,FpX\x19{\x22type\x22:\x22test\x22,\x22count\x22:2}";
@@ -0,0 +1,289 @@
+//! Construct messages programmatically, for testing code built on the validators.
+//!
+//! [`MessageBuilder`] produces `KVT` messages with the fields in the signed order and a `key`
+//! which is the hash of the `value`, so that they pass validation. A single field can be corrupted
+//! (see [`Corruption`]) to produce a message which fails one specific check. Unless it is set, the
+//! `signature` is a placeholder, so the messages do not pass signature verification.
+//!
+//! Requires the `test_util` feature.
+use ssb_legacy_msg_data::{
+    json::to_vec,
+    value::{ContentValue, RidiculousStringMap, Value},
+    LegacyF64,
+};
+use ssb_multiformats::multihash::Multihash;
+
+use crate::message::SsbMessage;
+use crate::message_value::SsbMessageValue;
+use crate::utils;
+
+/// The author of messages built by [`MessageBuilder::default`].
+pub const DEFAULT_AUTHOR: &str = "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519";
+
+/// The author given to a message built with [`Corruption::Author`].
+const OTHER_AUTHOR: &str = "@8HsIHUvTaWg8IXHpsb8dmDtKH8qLOrSNwNm298OkGoY=.ed25519";
+
+/// The `signature` of built messages: 64 zero bytes.
+const PLACEHOLDER_SIGNATURE: &str =
+    "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA==.sig.ed25519";
+
+/// A field of a built message to corrupt, so that the message fails validation.
+///
+/// Apart from [`Corruption::Key`], the `key` is computed after corrupting the `value`, so only the
+/// check of the corrupted field fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Corruption {
+    /// The `key` is not the hash of the `value` (`Error::ActualHashDidNotMatchKey`).
+    Key,
+    /// The `previous` is set on a first message, or does not match the previous message otherwise.
+    Previous,
+    /// The `author` differs from the author of the previous message
+    /// (`Error::AuthorsDidNotMatch`).
+    Author,
+    /// The `sequence` is one greater than it should be.
+    Sequence,
+    /// The `timestamp` is negative (`Error::InvalidTimestamp`).
+    Timestamp,
+    /// The `hash` is not `sha256` (`Error::InvalidHashFunction`).
+    Hash,
+    /// The `content` is a private message which is not canonical base64 (`Error::InvalidBase64`).
+    Content,
+}
+
+/// Builder for a message of a feed.
+///
+/// # Example
+///```
+///use ssb_validate::message::validate_message_hash_chain;
+///use ssb_validate::test_util::{Corruption, MessageBuilder};
+///
+///let first = MessageBuilder::default();
+///let second = first.next();
+///assert!(validate_message_hash_chain(second.build(), Some(first.build())).is_ok());
+///
+///let forked = first.next().corrupt(Corruption::Previous);
+///assert!(validate_message_hash_chain(forked.build(), Some(first.build())).is_err());
+///```
+#[derive(Debug, Clone)]
+pub struct MessageBuilder {
+    author: String,
+    sequence: u64,
+    previous: Option<Multihash>,
+    timestamp: f64,
+    content: Value,
+    signature: String,
+    corruption: Option<Corruption>,
+}
+
+impl Default for MessageBuilder {
+    /// The first message of the feed of [`DEFAULT_AUTHOR`].
+    fn default() -> Self {
+        MessageBuilder::new(DEFAULT_AUTHOR)
+    }
+}
+
+impl MessageBuilder {
+    /// The first message of the feed of `author`, with a `post` as content.
+    pub fn new(author: &str) -> Self {
+        let mut content = RidiculousStringMap::with_capacity(2);
+        content.insert("type".to_owned(), Value::String("post".to_owned()));
+        content.insert("text".to_owned(), Value::String(String::new()));
+
+        MessageBuilder {
+            author: author.to_owned(),
+            sequence: 1,
+            previous: None,
+            timestamp: 1_470_186_877_575.0,
+            content: Value::Object(content),
+            signature: PLACEHOLDER_SIGNATURE.to_owned(),
+            corruption: None,
+        }
+    }
+
+    /// Set the `sequence` of the message.
+    pub fn sequence(mut self, sequence: u64) -> Self {
+        self.sequence = sequence;
+        self
+    }
+
+    /// Set the `previous` of the message.
+    pub fn previous(mut self, previous: Option<Multihash>) -> Self {
+        self.previous = previous;
+        self
+    }
+
+    /// Set the `timestamp` of the message. Must be a finite number.
+    pub fn timestamp(mut self, timestamp: f64) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Set the `content` of the message.
+    pub fn content(mut self, content: Value) -> Self {
+        self.content = content;
+        self
+    }
+
+    /// Set the `signature` of the message. Defaults to a placeholder.
+    pub fn signature(mut self, signature: &str) -> Self {
+        self.signature = signature.to_owned();
+        self
+    }
+
+    /// Corrupt a field of the message.
+    pub fn corrupt(mut self, corruption: Corruption) -> Self {
+        self.corruption = Some(corruption);
+        self
+    }
+
+    /// The message which follows this message in the feed, with the same content.
+    ///
+    /// The corruption (if any) is not carried over.
+    pub fn next(&self) -> Self {
+        MessageBuilder {
+            sequence: self.sequence + 1,
+            previous: Some(self.key()),
+            corruption: None,
+            ..self.clone()
+        }
+    }
+
+    /// The `value` of the message, with any corruption applied.
+    pub fn message_value(&self) -> SsbMessageValue {
+        let mut value = SsbMessageValue {
+            previous: self.previous.clone(),
+            author: self.author.clone(),
+            sequence: self.sequence,
+            timestamp: LegacyF64::from_f64(self.timestamp).expect("timestamp must be finite"),
+            hash: "sha256".to_owned(),
+            content: ContentValue(self.content.clone()),
+            signature: self.signature.clone(),
+        };
+
+        match self.corruption {
+            Some(Corruption::Previous) => {
+                value.previous = Some(Multihash::Message([0; 32]));
+            }
+            Some(Corruption::Author) => value.author = OTHER_AUTHOR.to_owned(),
+            Some(Corruption::Sequence) => value.sequence = value.sequence.wrapping_add(1),
+            Some(Corruption::Timestamp) => {
+                value.timestamp = LegacyF64::from_f64(-1.0).unwrap();
+            }
+            Some(Corruption::Hash) => value.hash = "sha512".to_owned(),
+            Some(Corruption::Content) => {
+                value.content = ContentValue(Value::String("!.box".to_owned()));
+            }
+            Some(Corruption::Key) | None => {}
+        }
+
+        value
+    }
+
+    /// The `key` of the message (the hash of its `value`), with any corruption applied.
+    pub fn key(&self) -> Multihash {
+        match self.corruption {
+            Some(Corruption::Key) => Multihash::Message([0; 32]),
+            _ => utils::multihash_from_bytes(&self.value_bytes()),
+        }
+    }
+
+    /// The JSON encoding of the `value` of the message, as it is validated by the `message_value`
+    /// functions.
+    pub fn value_bytes(&self) -> Vec<u8> {
+        // Serializing a message value does not fail.
+        to_vec(&self.message_value(), false).unwrap_or_default()
+    }
+
+    /// The JSON encoding of the `KVT` message, as it is validated by the `message` functions.
+    pub fn build(&self) -> Vec<u8> {
+        let message = SsbMessage {
+            key: self.key(),
+            value: self.message_value(),
+            timestamp: None,
+        };
+
+        to_vec(&message, false).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::error::Error;
+    use crate::message::{validate_message_hash_chain, validate_multi_author_message_hash_chain};
+    use crate::message_value::{validate_message_value_hash_chain, SsbMessageValue};
+    use crate::test_data::MESSAGE_VALUE_1;
+    use crate::test_util::{Corruption, MessageBuilder};
+
+    #[test]
+    fn it_builds_valid_messages() {
+        let first = MessageBuilder::default();
+        assert!(validate_message_hash_chain::<_, &[u8]>(first.build(), None).is_ok());
+
+        let second = first.next();
+        let third = second.next();
+        assert!(validate_message_hash_chain(second.build(), Some(first.build())).is_ok());
+        assert!(validate_message_hash_chain(third.build(), Some(second.build())).is_ok());
+        assert!(
+            validate_message_value_hash_chain(third.value_bytes(), Some(second.value_bytes()))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn it_hashes_values_like_published_messages() {
+        let value = SsbMessageValue::try_from(MESSAGE_VALUE_1).unwrap();
+        let builder = MessageBuilder::new(&value.author)
+            .timestamp(f64::from(value.timestamp))
+            .content(value.content.0)
+            .signature(&value.signature);
+        assert_eq!(builder.value_bytes(), MESSAGE_VALUE_1.as_bytes());
+        assert_eq!(
+            builder.key().to_legacy_string(),
+            "%/v5mCnV/kmnVtnF3zXtD4tbzoEQo4kRq/0d/bgxP1WI=.sha256"
+        );
+    }
+
+    #[test]
+    fn it_corrupts_the_chosen_field() {
+        let first = MessageBuilder::default();
+        let check = |corruption| {
+            let message = first.next().corrupt(corruption).build();
+            validate_message_hash_chain(message, Some(first.build()))
+        };
+
+        match check(Corruption::Key) {
+            Err(Error::ActualHashDidNotMatchKey { .. }) => {}
+            _ => panic!(),
+        }
+        match check(Corruption::Previous) {
+            Err(Error::ForkedFeed { previous_seq: 1 }) => {}
+            _ => panic!(),
+        }
+        match check(Corruption::Author) {
+            Err(Error::AuthorsDidNotMatch { .. }) => {}
+            _ => panic!(),
+        }
+        match check(Corruption::Sequence) {
+            Err(Error::InvalidSequenceNumber { .. }) => {}
+            _ => panic!(),
+        }
+        match check(Corruption::Timestamp) {
+            Err(Error::InvalidTimestamp { .. }) => {}
+            _ => panic!(),
+        }
+        match check(Corruption::Hash) {
+            Err(Error::InvalidHashFunction { .. }) => {}
+            _ => panic!(),
+        }
+        match check(Corruption::Content) {
+            Err(Error::InvalidBase64 { .. }) => {}
+            _ => panic!(),
+        }
+
+        // Corrupted fields other than the key are hashed, so the message is self-consistent.
+        let message = first.next().corrupt(Corruption::Author).build();
+        assert!(validate_multi_author_message_hash_chain(message).is_ok());
+    }
+}
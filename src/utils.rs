@@ -1,35 +1,182 @@
 //! Helper functions used during validation computations.
+use std::collections::HashMap;
+
 use lazy_static::lazy_static;
 use regex::{bytes::Regex as RegexBytes, Regex};
+use serde::Deserialize;
 use sha2::{Digest, Sha256};
-use snafu::ResultExt;
-use ssb_legacy_msg_data::json;
-use ssb_multiformats::multihash::Multihash;
+use snafu::{ensure, OptionExt, ResultExt};
+use ssb_legacy_msg_data::{json, value::Value};
+use ssb_multiformats::{multifeed::Multifeed, multihash::Multihash};
 
-use crate::error::{InvalidMessageCouldNotSerializeValue, Result};
+use crate::error::{
+    ActualHashDidNotMatchKey, AuthorsDidNotMatch, FirstMessageDidNotHavePreviousOfNull,
+    FirstMessageDidNotHaveSequenceOfOne, ForkedFeed, InvalidMessage,
+    InvalidMessageCouldNotSerializeValue, InvalidSequenceNumber, PreviousWasNull, Result,
+    SequenceOverflow,
+};
 use crate::message_value::SsbMessageValue;
 
-/// Check that the given string represents canonical base64.
+/// The maximum length of a message value, in UTF-16 code units, when serialized as JSON (see
+/// [`message_value_length`]), as set by the feed specification.
+pub const MAX_MESSAGE_VALUE_UTF16_UNITS: usize = 8192;
+
+/// The UTF-8 encoding of the byte order mark (`U+FEFF`), which some publishers prefix to JSON.
+pub(crate) const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+
+lazy_static! {
+    /// Matches canonical base64 for `.box` private messages (see [`is_canonical_base64`]).
+    pub(crate) static ref CANONICAL_BASE64_RE: Regex = Regex::new(r"^(?:[a-zA-Z0-9/+]{4})*(?:[a-zA-Z0-9/+](?:(?:[AQgw]==)|(?:[a-zA-Z0-9/+][AEIMQUYcgkosw048]=)))?\.box$").unwrap();
+    /// Matches canonical base64 for `.box2` private messages (see [`is_canonical_base64_box2`]).
+    pub(crate) static ref CANONICAL_BASE64_BOX2_RE: Regex = Regex::new(r"^(?:[a-zA-Z0-9/+]{4})*(?:[a-zA-Z0-9/+](?:(?:[AQgw]==)|(?:[a-zA-Z0-9/+][AEIMQUYcgkosw048]=)))?\.box2$").unwrap();
+    /// Matches the expected order of message value fields (see [`is_correct_order`]).
+    pub(crate) static ref MESSAGE_VALUE_ORDER_RE: RegexBytes = RegexBytes::new(r#""previous"[\s\S]*("author"|"sequence")[\s\S]*("author"|"sequence")[\s\S]*"timestamp"[\s\S]*"hash"[\s\S]*"content"[\s\S]*"signature""#).unwrap();
+    /// Matches the field orders tolerated by the JavaScript implementation (see
+    /// [`is_legacy_js_order`]).
+    pub(crate) static ref LEGACY_JS_ORDER_RE: RegexBytes = RegexBytes::new(r#""previous"[\s\S]*("author"|"sequence")[\s\S]*("author"|"sequence")[\s\S]*"timestamp"[\s\S]*"hash"[\s\S]*"content""#).unwrap();
+}
+
+/// Encryption scheme of a private message, as identified by the suffix of its `content` string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncScheme {
+    /// [private-box](https://ssbc.github.io/scuttlebutt-protocol-guide/#private-messages) (`.box`).
+    Box1,
+    /// [private-group](https://github.com/ssbc/private-group-spec) box2 (`.box2`).
+    Box2,
+}
+
+/// Determine the encryption scheme of an encrypted message `content` string from its suffix.
+///
+/// Returns `None` if the string does not end with a known suffix (`.box` or `.box2`). Note that
+/// this does not check the encoding of the ciphertext; see [`is_canonical_base64`] and
+/// [`is_canonical_base64_box2`].
+pub fn encryption_scheme(content: &str) -> Option<EncScheme> {
+    if content.ends_with(".box") {
+        Some(EncScheme::Box1)
+    } else if content.ends_with(".box2") {
+        Some(EncScheme::Box2)
+    } else {
+        None
+    }
+}
+
+/// Check that the given string represents canonical base64 of a
+/// [private-box](https://ssbc.github.io/scuttlebutt-protocol-guide/#private-messages) message.
 ///
 /// A Regex pattern is used to match on canonical base64 for private messages. This has been
 /// implemented according to the [`is-canonical-base64` JS module](https://www.npmjs.com/package/is-canonical-base64) by Dominic Tarr.
+/// The base64 must be followed by a `.box` suffix, with nothing after it. See
+/// [`is_canonical_base64_box2`] for `.box2` messages.
 pub fn is_canonical_base64(private_msg: &str) -> bool {
-    lazy_static! {
-        static ref RE: Regex = Regex::new(r"^(?:[a-zA-Z0-9/+]{4})*(?:[a-zA-Z0-9/+](?:(?:[AQgw]==)|(?:[a-zA-Z0-9/+][AEIMQUYcgkosw048]=)))?.box.*$").unwrap();
-    }
-    RE.is_match(private_msg)
+    CANONICAL_BASE64_RE.is_match(private_msg)
 }
 
-/// Check that the length of the given message - when serialized as JSON - is less than 8192 UTF-16 code units.
+/// Check that the given string represents canonical base64 of a
+/// [private-group](https://github.com/ssbc/private-group-spec) (box2) message.
+///
+/// The base64 must be followed by a `.box2` suffix, with nothing after it. See
+/// [`is_canonical_base64`] for `.box` messages.
+pub fn is_canonical_base64_box2(private_msg: &str) -> bool {
+    CANONICAL_BASE64_BOX2_RE.is_match(private_msg)
+}
+
+/// Check that the length of the given message - when serialized as JSON - does not exceed
+/// [`MAX_MESSAGE_VALUE_UTF16_UNITS`] UTF-16 code units.
 pub fn is_correct_length(msg_value: &SsbMessageValue) -> Result<bool> {
+    let msg_len = message_value_length(msg_value)?;
+    if msg_len > MAX_MESSAGE_VALUE_UTF16_UNITS {
+        Ok(false)
+    } else {
+        Ok(true)
+    }
+}
+
+/// Return the length of the given message value - when serialized as JSON - in UTF-16 code units.
+///
+/// This is the length which is limited to [`MAX_MESSAGE_VALUE_UTF16_UNITS`] by the feed
+/// specification (see [`is_correct_length`]).
+pub fn message_value_length(msg_value: &SsbMessageValue) -> Result<usize> {
     // the second arg is used to set `compact` to `false` (preserves whitespace)
     let msg_value_str =
         json::to_string(msg_value, false).context(InvalidMessageCouldNotSerializeValue)?;
-    let msg_len: usize = msg_value_str.chars().map(|ch| ch.len_utf16()).sum();
-    if msg_len > 8192 {
-        Ok(false)
+    Ok(utf16_byte_length(&msg_value_str))
+}
+
+/// Return the length of the given message value bytes - when serialized as JSON - in UTF-16 code
+/// units.
+///
+/// The bytes are decoded as a message value (without validating it) and the length is computed
+/// as in [`message_value_length`]. This is useful for enforcing storage quotas, or rejecting
+/// oversized messages, before performing a full validation.
+pub fn message_value_utf16_length(bytes: &[u8]) -> Result<usize> {
+    let msg_value = json::from_slice::<SsbMessageValue>(bytes).context(InvalidMessage {
+        message: bytes.to_owned(),
+    })?;
+    message_value_length(&msg_value)
+}
+
+/// Check that the given string is a valid SSB reference: a feed (`@...`), message (`%...`) or blob
+/// (`&...`) identifier in the legacy encoding.
+pub fn is_valid_link(link: &str) -> bool {
+    let tail = if link.starts_with('@') {
+        Multifeed::from_legacy(link.as_bytes())
+            .map(|(_, tail)| tail)
+            .ok()
     } else {
-        Ok(true)
+        Multihash::from_legacy(link.as_bytes())
+            .map(|(_, tail)| tail)
+            .ok()
+    };
+    matches!(tail, Some(tail) if tail.is_empty())
+}
+
+/// Serialize the given message value in the form which is hashed and signed.
+///
+/// This is the non-compact JSON encoding (two-space indentation) of the message value. The `key`
+/// of the message is the [`multihash_from_bytes`] of these bytes (with the `signature` included),
+/// and the signature is computed over these bytes with the `signature` omitted. Note that the
+/// fields are serialized in the standard order (`author` before `sequence`), so the bytes differ
+/// from the original encoding of a message which has these two fields swapped.
+pub fn canonical_value_bytes(msg_value: &SsbMessageValue) -> Result<Vec<u8>> {
+    // the second arg is used to set `compact` to `false` (preserves whitespace)
+    json::to_vec(msg_value, false).context(InvalidMessageCouldNotSerializeValue)
+}
+
+/// Check that the given string is a message hash (`%...sha256`) in the legacy encoding.
+pub fn is_message_hash(hash: &str) -> bool {
+    matches!(
+        Multihash::from_legacy(hash.as_bytes()),
+        Ok((Multihash::Message(_), tail)) if tail.is_empty()
+    )
+}
+
+/// Check that the given string is a blob hash (`&...sha256`) in the legacy encoding.
+pub fn is_blob_hash(hash: &str) -> bool {
+    matches!(
+        Multihash::from_legacy(hash.as_bytes()),
+        Ok((Multihash::Blob(_), tail)) if tail.is_empty()
+    )
+}
+
+/// Check whether the given value is nested more than `max_depth` levels deep.
+///
+/// Each object or array counts as one level of nesting, so a flat object has a depth of 1 and a
+/// scalar value has a depth of 0. The walk stops as soon as the limit is exceeded.
+pub fn is_deeper_than(value: &Value, max_depth: usize) -> bool {
+    match value {
+        Value::Object(object) => {
+            max_depth == 0
+                || object
+                    .iter()
+                    .any(|(_, value)| is_deeper_than(value, max_depth - 1))
+        }
+        Value::Array(array) => {
+            max_depth == 0
+                || array
+                    .iter()
+                    .any(|value| is_deeper_than(value, max_depth - 1))
+        }
+        _ => false,
     }
 }
 
@@ -40,10 +187,191 @@ pub fn is_correct_length(msg_value: &SsbMessageValue) -> Result<bool> {
 /// is used to match on the order of the fields. The order of the second and third fields (`"author"` and
 /// `"sequence"`) can be reversed. For more information on this and other quirks, you may wish to peruse the issues and code for the JavaScript [ssb-validate library](https://github.com/ssb-js/ssb-validate).
 pub fn is_correct_order(bytes: &[u8]) -> bool {
-    lazy_static! {
-        static ref RE_B: RegexBytes = RegexBytes::new(r#""previous"[\s\S]*("author"|"sequence")[\s\S]*("author"|"sequence")[\s\S]*"timestamp"[\s\S]*"hash"[\s\S]*"content"[\s\S]*"signature""#).unwrap();
+    MESSAGE_VALUE_ORDER_RE.is_match(bytes)
+}
+
+/// Check that the top-level fields of the given message value are in an order tolerated by the
+/// JavaScript implementation.
+///
+/// In addition to the orders accepted by [`is_correct_order`], the `"signature"` may appear at any
+/// position. The JavaScript [ssb-keys](https://github.com/ssb-js/ssb-keys) `verifyObj` deletes the
+/// `signature` from the message value before verifying it against the remaining fields, so the
+/// position of the `signature` was never checked. The other fields must still be in the expected
+/// order.
+pub fn is_legacy_js_order(bytes: &[u8]) -> bool {
+    MESSAGE_VALUE_ORDER_RE.is_match(bytes) || LEGACY_JS_ORDER_RE.is_match(bytes)
+}
+
+/// Return the top-level fields (keys) of the given message value in the order in which they
+/// appear.
+///
+/// The bytes may represent either a message value or a `KVT` message, in which case the fields of
+/// the `value` are returned. An empty vector is returned if the bytes cannot be parsed as a JSON
+/// object. This is used to report the actual field order when [`is_correct_order`] fails.
+pub fn message_value_key_order(bytes: &[u8]) -> Vec<String> {
+    let object = match json::from_slice::<Value>(bytes) {
+        Ok(Value::Object(object)) => object,
+        _ => return Vec::new(),
+    };
+    let object = match object.get("value") {
+        Some(Value::Object(value)) => value,
+        _ => &object,
+    };
+    object.iter().map(|(key, _)| key.to_owned()).collect()
+}
+
+/// Re-serialize the given message value with its fields in the canonical order.
+///
+/// This is a repair tool for feeds published by clients which emit the fields out of order (see
+/// [`is_correct_order`]). The reordered bytes have a different hash (and the `signature` no longer
+/// matches them), so they must be re-signed and republished; never reorder a message before
+/// comparing its hash with a `key` or `previous`.
+pub fn reorder_message_value(bytes: &[u8]) -> Result<Vec<u8>> {
+    let msg_value = json::from_slice::<SsbMessageValue>(bytes).context(InvalidMessage {
+        message: bytes.to_owned(),
+    })?;
+    canonical_value_bytes(&msg_value)
+}
+
+/// The `author` and `sequence` fields of a message value, ignoring all other fields.
+#[derive(Deserialize)]
+struct AuthorSequence {
+    author: String,
+    sequence: u64,
+}
+
+/// The `author` and `sequence` fields of either a message value or the `value` of a `KVT` message.
+#[derive(Deserialize)]
+struct AuthorSequencePeek {
+    author: Option<String>,
+    sequence: Option<u64>,
+    value: Option<AuthorSequence>,
+}
+
+/// Extract the `author` and `sequence` of a message without performing any validation.
+///
+/// The bytes may represent either a message value or a `KVT` message. Only the `author` and
+/// `sequence` fields are decoded, which makes this cheaper than a full decode of the message. This
+/// is useful for grouping messages by author and sorting them by sequence before validating them
+/// (for example, with the out-of-order functions).
+pub fn peek_author_sequence(bytes: &[u8]) -> Result<(String, u64)> {
+    let peek = json::from_slice::<AuthorSequencePeek>(bytes).context(InvalidMessage {
+        message: bytes.to_owned(),
+    })?;
+
+    match peek {
+        AuthorSequencePeek {
+            value: Some(AuthorSequence { author, sequence }),
+            ..
+        }
+        | AuthorSequencePeek {
+            author: Some(author),
+            sequence: Some(sequence),
+            ..
+        } => Ok((author, sequence)),
+        // Decode again, requiring the fields, to report which field is missing.
+        _ => {
+            let AuthorSequence { author, sequence } = json::from_slice::<AuthorSequence>(bytes)
+                .context(InvalidMessage {
+                    message: bytes.to_owned(),
+                })?;
+            Ok((author, sequence))
+        }
+    }
+}
+
+/// A rolling `sha256` digest of the keys of a feed, for comparing replicas.
+///
+/// Two peers which have validated the same messages of a feed, in the same order, produce the same
+/// digest. Each key is hashed in its legacy encoding (for example
+/// `%kLWDux4wCG+OdQWAHnpBGzGlCehqMLfgLbzlKCvgesU=.sha256`).
+///
+/// # Example
+///```
+///use ssb_multiformats::multihash::Multihash;
+///use ssb_validate::utils::FeedDigest;
+///
+///let (key, _) =
+///    Multihash::from_legacy(b"%/v5mCnV/kmnVtnF3zXtD4tbzoEQo4kRq/0d/bgxP1WI=.sha256").unwrap();
+///let mut digest = FeedDigest::new();
+///digest.update(&key);
+///let digest: [u8; 32] = digest.finalize();
+///```
+#[derive(Debug, Clone, Default)]
+pub struct FeedDigest {
+    hasher: Sha256,
+}
+
+impl FeedDigest {
+    /// Create a digest of an empty feed.
+    pub fn new() -> Self {
+        FeedDigest::default()
+    }
+
+    /// Add the key of the next validated message of the feed.
+    pub fn update(&mut self, key: &Multihash) {
+        self.hasher.input(key.to_legacy_vec());
+    }
+
+    /// Return the digest of all the keys added so far.
+    pub fn finalize(self) -> [u8; 32] {
+        self.hasher.result().into()
     }
-    RE_B.is_match(bytes)
+}
+
+/// Group a collection of messages by author, returning the indices of the messages of each author
+/// ordered by ascending sequence number.
+///
+/// The messages may be message values or `KVT` messages. Only the `author` and `sequence` of each
+/// message are decoded (see [`peek_author_sequence`]); no other validation is performed. Each
+/// group can then be passed to the single-author validation functions, such as
+/// [`par_validate_message_hash_chain_of_feed`](crate::message::par_validate_message_hash_chain_of_feed).
+pub fn group_by_author<T: AsRef<[u8]>>(messages: &[T]) -> Result<HashMap<String, Vec<usize>>> {
+    let mut groups: HashMap<String, Vec<(u64, usize)>> = HashMap::new();
+    for (idx, msg) in messages.iter().enumerate() {
+        let (author, sequence) = peek_author_sequence(msg.as_ref())?;
+        groups.entry(author).or_default().push((sequence, idx));
+    }
+
+    let groups = groups
+        .into_iter()
+        .map(|(author, mut group)| {
+            group.sort_unstable();
+            (author, group.into_iter().map(|(_, idx)| idx).collect())
+        })
+        .collect();
+
+    Ok(groups)
+}
+
+/// Check that a collection of messages are all by the same author, returning that author.
+///
+/// The messages may be message values or `KVT` messages. Only the `author` and `sequence` of each
+/// message are decoded (see [`peek_author_sequence`]); no other validation is performed. The first
+/// message whose author differs from the author of the first message is reported as
+/// `Error::AuthorsDidNotMatch`. An empty collection has no author, so an empty string is returned.
+pub fn assert_single_author<T: AsRef<[u8]>>(messages: &[T]) -> Result<String> {
+    let mut authors = messages
+        .iter()
+        .map(|msg| peek_author_sequence(msg.as_ref()).map(|(author, _)| author));
+
+    let first_author = match authors.next() {
+        Some(author) => author?,
+        None => return Ok(String::new()),
+    };
+
+    for author in authors {
+        let author = author?;
+        ensure!(
+            author == first_author,
+            AuthorsDidNotMatch {
+                previous_author: first_author,
+                author
+            }
+        );
+    }
+
+    Ok(first_author)
 }
 
 /// Generate a hash for a given message value.
@@ -58,13 +386,357 @@ pub fn multihash_from_bytes(bytes: &[u8]) -> Multihash {
     Multihash::Message(value_hash.into())
 }
 
+/// A strategy for computing the key of a message from its serialized `value`.
+///
+/// The validators use [`LegacyHasher`] unless a hasher is passed explicitly (for example, to
+/// [`validate_message_hash_chain_with_hasher`](crate::message::validate_message_hash_chain_with_hasher)).
+/// A custom hasher allows validating feeds of networks which use a different hashing convention.
+/// `value_bytes` is always valid UTF-8.
+pub trait MessageHasher {
+    fn hash(&self, value_bytes: &[u8]) -> Multihash;
+}
+
+/// The hashing convention of the classic network (see [`multihash_from_bytes`]).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LegacyHasher;
+
+impl MessageHasher for LegacyHasher {
+    fn hash(&self, value_bytes: &[u8]) -> Multihash {
+        multihash_from_bytes(value_bytes)
+    }
+}
+
+/// Check that the `key` of a message matches the hash of its serialized `value`.
+///
+/// `value_bytes` is the message value in the form in which it is hashed (see
+/// [`canonical_value_bytes`]). The hash is computed as for [`multihash_from_bytes`] and compared
+/// with `key`, returning `Error::ActualHashDidNotMatchKey` (holding `value_bytes`) on mismatch.
+/// No other validation is performed.
+pub fn verify_key_binding(key: &Multihash, value_bytes: &[u8]) -> Result<()> {
+    verify_key_binding_with_hasher(key, value_bytes, &LegacyHasher)
+}
+
+/// Check that the `key` of a message matches the hash of its serialized `value`, as computed by
+/// `hasher`.
+///
+/// See [`verify_key_binding`].
+pub fn verify_key_binding_with_hasher(
+    key: &Multihash,
+    value_bytes: &[u8],
+    hasher: &dyn MessageHasher,
+) -> Result<()> {
+    // Bytes which are not UTF-8 cannot be the encoding of a message value, so the lossy conversion
+    // (which borrows when the bytes are valid) only ever produces a hash which does not match.
+    let actual_hash = hasher.hash(String::from_utf8_lossy(value_bytes).as_bytes());
+    ensure!(
+        actual_hash == *key,
+        ActualHashDidNotMatchKey {
+            message: value_bytes.to_owned(),
+            actual_hash,
+            expected_hash: key.clone(),
+        }
+    );
+
+    Ok(())
+}
+
+/// The fields of a message in a binary feed format (Bendy Butt or Gabby Grove) which are relevant
+/// to hash chain validation.
+pub(crate) struct BinaryMessage {
+    pub(crate) author: [u8; 32],
+    pub(crate) sequence: u64,
+    pub(crate) previous: Option<[u8; 32]>,
+    pub(crate) key: [u8; 32],
+}
+
+/// Check that a message of a binary feed format follows on from the previous message, or is the
+/// first message of its feed if there is no previous message.
+///
+/// `feed_id_string` formats an author for `Error::AuthorsDidNotMatch`.
+pub(crate) fn check_binary_continuity(
+    message: &BinaryMessage,
+    previous: Option<&BinaryMessage>,
+    message_bytes: &[u8],
+    feed_id_string: fn(&[u8; 32]) -> String,
+) -> Result<()> {
+    match previous {
+        Some(previous) => {
+            // The authors are not allowed to change in a feed.
+            ensure!(
+                message.author == previous.author,
+                AuthorsDidNotMatch {
+                    previous_author: feed_id_string(&previous.author),
+                    author: feed_id_string(&message.author)
+                }
+            );
+
+            // The sequence must increase by one.
+            let expected_sequence = previous.sequence.checked_add(1).context(SequenceOverflow {
+                message: message_bytes.to_owned(),
+            })?;
+            ensure!(
+                message.sequence == expected_sequence,
+                InvalidSequenceNumber {
+                    message: message_bytes.to_owned(),
+                    actual: message.sequence,
+                    expected: expected_sequence
+                }
+            );
+
+            // The previous must match the key of the previous message otherwise it's a fork.
+            match message.previous {
+                Some(previous_key) => ensure!(
+                    previous_key == previous.key,
+                    ForkedFeed {
+                        previous_seq: previous.sequence
+                    }
+                ),
+                None => return PreviousWasNull.fail(),
+            }
+        }
+        None => {
+            // This message is the first message.
+            ensure!(
+                message.sequence == 1,
+                FirstMessageDidNotHaveSequenceOfOne {
+                    message: message_bytes.to_owned()
+                }
+            );
+            ensure!(
+                message.previous.is_none(),
+                FirstMessageDidNotHavePreviousOfNull {
+                    message: message_bytes.to_owned()
+                }
+            );
+        }
+    }
+
+    Ok(())
+}
+
 /// FML, scuttlebutt is miserable.
 ///
 /// This is what node's `Buffer.new(messageString, 'binary')` does. Who knew?
 /// So, surprise, but the way ssb encodes messages for signing vs the way it encodes them for
 /// hashing is different.
+///
+/// The text is encoded as UTF-16 and each code unit is then truncated to its low byte, so the
+/// output always contains exactly [`utf16_byte_length`] bytes. Any tooling which computes message
+/// hashes must reproduce this exactly.
 pub fn node_buffer_binary_serializer(text: &str) -> Vec<u8> {
     text.encode_utf16()
         .map(|word| (word & 0xFF) as u8)
         .collect()
 }
+
+/// Return the length of the given text in UTF-16 code units.
+///
+/// This is the length used by the JavaScript implementation when checking the size of a message
+/// value, and is also the number of bytes produced by [`node_buffer_binary_serializer`].
+pub fn utf16_byte_length(text: &str) -> usize {
+    text.chars().map(|ch| ch.len_utf16()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use ssb_legacy_msg_data::{json, value::Value};
+    use ssb_multiformats::multihash::Multihash;
+
+    use crate::error::Error;
+    use crate::message::SsbMessage;
+    use crate::message_value::SsbMessageValue;
+    use crate::test_data::{
+        MESSAGE_1, MESSAGE_2, MESSAGE_2_INCORRECT_AUTHOR, MESSAGE_3, MESSAGE_VALUE_1,
+    };
+    use crate::utils::{
+        assert_single_author, canonical_value_bytes, group_by_author, is_canonical_base64,
+        is_canonical_base64_box2, is_correct_order, is_deeper_than, is_legacy_js_order,
+        is_valid_link, message_value_utf16_length, multihash_from_bytes,
+        node_buffer_binary_serializer, peek_author_sequence, reorder_message_value,
+        utf16_byte_length, verify_key_binding,
+    };
+
+    #[test]
+    fn node_buffer_binary_serializer_matches_node() {
+        // Expected output taken from `Buffer.from(text, 'binary')` in node.
+        assert_eq!(node_buffer_binary_serializer("ssb"), b"ssb".to_vec());
+        assert_eq!(node_buffer_binary_serializer("é"), vec![0xe9]);
+        assert_eq!(node_buffer_binary_serializer("’"), vec![0x19]);
+        assert_eq!(node_buffer_binary_serializer("😀"), vec![0x3d, 0x00]);
+    }
+
+    #[test]
+    fn utf16_byte_length_matches_serialized_length() {
+        for text in &["ssb", "é", "’", "😀", "Web frameworks’ 😀"] {
+            assert_eq!(
+                utf16_byte_length(text),
+                node_buffer_binary_serializer(text).len()
+            );
+        }
+        assert_eq!(utf16_byte_length("😀"), 2);
+    }
+
+    #[test]
+    fn multihash_from_bytes_matches_known_key() {
+        let (expected, _) =
+            Multihash::from_legacy(b"%/v5mCnV/kmnVtnF3zXtD4tbzoEQo4kRq/0d/bgxP1WI=.sha256")
+                .unwrap();
+        assert_eq!(multihash_from_bytes(MESSAGE_VALUE_1.as_bytes()), expected);
+    }
+
+    #[test]
+    fn it_measures_the_utf16_length_of_message_value_bytes() {
+        let length = message_value_utf16_length(MESSAGE_VALUE_1.as_bytes()).unwrap();
+        assert_eq!(length, utf16_byte_length(MESSAGE_VALUE_1));
+        assert!(message_value_utf16_length(b"{}").is_err());
+    }
+
+    #[test]
+    fn it_measures_the_depth_of_a_value() {
+        let value: Value = json::from_slice(br#"{"a": [1, {"b": null}], "c": "d"}"#).unwrap();
+        assert!(!is_deeper_than(&value, 3));
+        assert!(is_deeper_than(&value, 2));
+        assert!(!is_deeper_than(&Value::Null, 0));
+    }
+
+    #[test]
+    fn it_peeks_the_author_and_sequence() {
+        let author = "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519".to_owned();
+        assert_eq!(
+            peek_author_sequence(MESSAGE_VALUE_1.as_bytes()).unwrap(),
+            (author.clone(), 1)
+        );
+        assert_eq!(
+            peek_author_sequence(MESSAGE_2.as_bytes()).unwrap(),
+            (author, 2)
+        );
+        assert!(peek_author_sequence(br#"{"author": "@a"}"#).is_err());
+    }
+
+    #[test]
+    fn it_checks_links() {
+        assert!(is_valid_link(
+            "&MxwsfZoq7X6oqnEX/TWIlAqd6S+jsUA6T1hqZYdl7RM=.sha256"
+        ));
+        assert!(is_valid_link(
+            "%kLWDux4wCG+OdQWAHnpBGzGlCehqMLfgLbzlKCvgesU=.sha256"
+        ));
+        assert!(is_valid_link(
+            "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519"
+        ));
+        assert!(!is_valid_link("&MxwsfZoq7X6oqnEX.sha256"));
+        assert!(!is_valid_link(
+            "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519 "
+        ));
+        assert!(!is_valid_link("#scuttlebutt"));
+    }
+
+    #[test]
+    fn it_serializes_the_canonical_value_bytes() {
+        let message: SsbMessage = json::from_slice(MESSAGE_1.as_bytes()).unwrap();
+        let message_value: SsbMessageValue = json::from_slice(MESSAGE_VALUE_1.as_bytes()).unwrap();
+        let bytes = canonical_value_bytes(&message_value).unwrap();
+        assert_eq!(bytes, MESSAGE_VALUE_1.as_bytes());
+        assert_eq!(multihash_from_bytes(&bytes), message.key);
+    }
+
+    #[test]
+    fn it_rejects_trailing_data_after_the_box_suffix() {
+        assert!(is_canonical_base64("siZEm1zF.box"));
+        assert!(is_canonical_base64_box2("siZEm1zF.box2"));
+        assert!(!is_canonical_base64("siZEm1zF.box GARBAGE"));
+        assert!(!is_canonical_base64_box2("siZEm1zF.box2 GARBAGE"));
+        assert!(!is_canonical_base64("siZEm1zF.box2.box3"));
+        assert!(!is_canonical_base64("siZEm1zFxbox"));
+    }
+
+    #[test]
+    fn it_checks_each_encryption_scheme_against_its_own_suffix() {
+        assert!(!is_canonical_base64("siZEm1zF.box2"));
+        assert!(!is_canonical_base64_box2("siZEm1zF.box"));
+        assert!(!is_canonical_base64_box2("siZEm1z.box2"));
+    }
+
+    #[test]
+    fn it_verifies_the_key_binding() {
+        let message: SsbMessage = json::from_slice(MESSAGE_1.as_bytes()).unwrap();
+        assert!(verify_key_binding(&message.key, MESSAGE_VALUE_1.as_bytes()).is_ok());
+
+        let other: SsbMessage = json::from_slice(MESSAGE_2.as_bytes()).unwrap();
+        match verify_key_binding(&other.key, MESSAGE_VALUE_1.as_bytes()) {
+            Err(Error::ActualHashDidNotMatchKey {
+                message: _,
+                actual_hash,
+                expected_hash,
+            }) => {
+                assert_eq!(actual_hash, message.key);
+                assert_eq!(expected_hash, other.key);
+            }
+            _ => panic!(),
+        }
+
+        assert!(verify_key_binding(&message.key, b"\xff\xfe").is_err());
+    }
+
+    #[test]
+    fn it_asserts_a_single_author() {
+        let author = "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519".to_owned();
+        assert_eq!(
+            assert_single_author(&[MESSAGE_1, MESSAGE_2, MESSAGE_3]).unwrap(),
+            author
+        );
+        assert_eq!(assert_single_author::<&str>(&[]).unwrap(), "");
+
+        match assert_single_author(&[MESSAGE_1, MESSAGE_2_INCORRECT_AUTHOR]) {
+            Err(Error::AuthorsDidNotMatch {
+                previous_author, ..
+            }) => assert_eq!(previous_author, author),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn it_groups_messages_by_author() {
+        let messages = [MESSAGE_3, MESSAGE_2_INCORRECT_AUTHOR, MESSAGE_1, MESSAGE_2];
+        let groups = group_by_author(&messages[..]).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(
+            groups["@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519"],
+            vec![2, 3, 0]
+        );
+        assert_eq!(
+            groups["@xzSRT0HSAqGuqu5HxJvqxtp2FJGpt5nRPIHMznLoBao=.ed25519"],
+            vec![1]
+        );
+    }
+
+    #[test]
+    fn it_reorders_message_value_fields() {
+        let value: Value = json::from_slice(MESSAGE_VALUE_1.as_bytes()).unwrap();
+        let object = match value {
+            Value::Object(object) => object,
+            _ => panic!(),
+        };
+        // Move the `signature` to the front.
+        let mut reordered = ssb_legacy_msg_data::value::RidiculousStringMap::with_capacity(7);
+        reordered.insert(
+            "signature".to_owned(),
+            object.get("signature").unwrap().clone(),
+        );
+        for (key, value) in object.iter().filter(|(key, _)| *key != "signature") {
+            reordered.insert(key.to_owned(), value.clone());
+        }
+        let misordered = json::to_vec(&Value::Object(reordered), false).unwrap();
+        assert!(!is_correct_order(&misordered));
+        assert!(is_legacy_js_order(&misordered));
+
+        let repaired = reorder_message_value(&misordered).unwrap();
+        assert!(is_correct_order(&repaired));
+        assert_eq!(repaired, MESSAGE_VALUE_1.as_bytes());
+
+        match reorder_message_value(b"[]") {
+            Err(Error::InvalidMessage { .. }) => {}
+            _ => panic!(),
+        }
+    }
+}
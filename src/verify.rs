@@ -0,0 +1,137 @@
+//! Functions which combine validation with signature verification (requires the `verify` feature).
+//!
+//! Validating and verifying a message separately means parsing it twice. The functions in this
+//! module verify the ed25519 signature of a message using the value already parsed during
+//! validation.
+//!
+//! Note that the encoding used to compute the signature of a message differs from the encoding
+//! used to compute its hash. The signature is computed over the message value *without* the
+//! `signature` field, serialized as (non-compact) JSON and encoded as UTF-8. The hash is computed
+//! over the complete message value, encoded with
+//! [`node_buffer_binary_serializer`](crate::utils::node_buffer_binary_serializer).
+use snafu::{ensure, ResultExt};
+use ssb_legacy_msg_data::{json::to_vec, value::Value};
+use ssb_multiformats::multikey::Multikey;
+
+use crate::error::{InvalidMessageCouldNotSerializeValue, InvalidSignature, Result};
+use crate::message::validate_message_hash_chain_returning_value;
+use crate::options::ValidationOptions;
+
+/// Validate a message in relation to the previous message and verify its signature.
+///
+/// It expects the messages to be the JSON encoded message of shape: `{key: "", value: {...}}`
+///
+/// This performs all of the checks of
+/// [`validate_message_hash_chain`](crate::message::validate_message_hash_chain), plus:
+/// - the `signature` was created by the keypair of the `author` over the message value
+///
+/// `previous_msg_bytes` will be `None` only when `message_bytes` is the first message by that author.
+pub fn validate_and_verify_message_hash_chain<T: AsRef<[u8]>, U: AsRef<[u8]>>(
+    message_bytes: T,
+    previous_msg_bytes: Option<U>,
+) -> Result<()> {
+    let message_bytes = message_bytes.as_ref();
+    let (_, message_value) = validate_message_hash_chain_returning_value(
+        message_bytes,
+        previous_msg_bytes,
+        &ValidationOptions::default(),
+    )?;
+
+    verify_message_value_signature(message_value, message_bytes)
+}
+
+/// Verify the signature of a message value which has already been validated.
+fn verify_message_value_signature(mut message_value: Value, message_bytes: &[u8]) -> Result<()> {
+    let (author, signature) = match message_value {
+        Value::Object(ref mut object) => {
+            match (
+                object.get("author").cloned(),
+                object.remove("signature".to_owned()),
+            ) {
+                (Some(Value::String(author)), Some(Value::String(signature))) => {
+                    (author, signature)
+                }
+                _ => {
+                    return InvalidSignature {
+                        message: message_bytes.to_owned(),
+                    }
+                    .fail()
+                }
+            }
+        }
+        _ => {
+            return InvalidSignature {
+                message: message_bytes.to_owned(),
+            }
+            .fail()
+        }
+    };
+
+    let key_and_signature = Multikey::from_legacy(author.as_bytes())
+        .ok()
+        .and_then(|(key, _)| {
+            key.sig_from_legacy(signature.as_bytes())
+                .ok()
+                .map(|(signature, _)| (key, signature))
+        });
+    let (key, signature) = match key_and_signature {
+        Some(key_and_signature) => key_and_signature,
+        None => {
+            return InvalidSignature {
+                message: message_bytes.to_owned(),
+            }
+            .fail()
+        }
+    };
+
+    // The signed bytes are the UTF-8 encoded value without the signature.
+    let signed_bytes =
+        to_vec(&message_value, false).context(InvalidMessageCouldNotSerializeValue)?;
+
+    ensure!(
+        key.is_signature_correct(&signed_bytes, &signature),
+        InvalidSignature {
+            message: message_bytes.to_owned()
+        }
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ssb_legacy_msg_data::{json::from_slice, value::Value};
+
+    use crate::error::Error;
+    use crate::test_data::{
+        MESSAGE_1, MESSAGE_2, MESSAGE_PRIVATE, MESSAGE_PRIVATE_PREV, MESSAGE_VALUE_2,
+        MESSAGE_WITH_UNICODE, MESSAGE_WITH_UNICODE_PREV,
+    };
+    use crate::verify::{validate_and_verify_message_hash_chain, verify_message_value_signature};
+
+    #[test]
+    fn it_validates_and_verifies_messages() {
+        assert!(validate_and_verify_message_hash_chain::<_, &[u8]>(MESSAGE_1, None).is_ok());
+        assert!(validate_and_verify_message_hash_chain(MESSAGE_2, Some(MESSAGE_1)).is_ok());
+        assert!(validate_and_verify_message_hash_chain(
+            MESSAGE_WITH_UNICODE,
+            Some(MESSAGE_WITH_UNICODE_PREV)
+        )
+        .is_ok());
+        assert!(validate_and_verify_message_hash_chain(
+            MESSAGE_PRIVATE,
+            Some(MESSAGE_PRIVATE_PREV)
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn it_detects_an_invalid_signature() {
+        let tampered = MESSAGE_VALUE_2.replace("\"size\": 642763", "\"size\": 642764");
+        let message_value: Value = from_slice(tampered.as_bytes()).unwrap();
+        match verify_message_value_signature(message_value, tampered.as_bytes()) {
+            Err(Error::InvalidSignature { message: _ }) => {}
+            _ => panic!(),
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Entry points for use from JavaScript via `wasm-bindgen` (requires the `wasm` feature).
+//!
+//! These functions wrap the validation functions of this crate, mapping any [`Error`] to its
+//! `Display` string so that the full error type need not cross the JavaScript boundary. The batch
+//! functions validate messages one at a time, so they are available without the `parallel`
+//! feature (which should be disabled when targeting `wasm32-unknown-unknown`).
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+use crate::message::{
+    validate_message_hash_chain, validate_message_hash_chain_in_batch,
+    validate_multi_author_message_hash_chain, validate_ooo_message_hash_chain,
+};
+use crate::message_value::{validate_message_value, validate_message_value_hash_chain};
+use crate::options::ValidationOptions;
+
+fn to_js_error(err: Error) -> String {
+    err.to_string()
+}
+
+/// Validate a single message (in isolation).
+///
+/// See [`validate_multi_author_message_hash_chain`].
+#[wasm_bindgen]
+pub fn validate_message_js(message: &[u8]) -> Result<(), String> {
+    validate_multi_author_message_hash_chain(message).map_err(to_js_error)
+}
+
+/// Validate a message in relation to the previous message.
+///
+/// See [`validate_message_hash_chain`].
+#[wasm_bindgen]
+pub fn validate_message_hash_chain_js(
+    message: &[u8],
+    previous: Option<Vec<u8>>,
+) -> Result<(), String> {
+    validate_message_hash_chain(message, previous).map_err(to_js_error)
+}
+
+/// Validate a single message value (in isolation).
+///
+/// See [`validate_message_value`].
+#[wasm_bindgen]
+pub fn validate_message_value_js(message_value: &[u8]) -> Result<(), String> {
+    validate_message_value(message_value).map_err(to_js_error)
+}
+
+/// Validate a message value in relation to the previous message value.
+///
+/// See [`validate_message_value_hash_chain`].
+#[wasm_bindgen]
+pub fn validate_message_value_hash_chain_js(
+    message_value: &[u8],
+    previous: Option<Vec<u8>>,
+) -> Result<(), String> {
+    validate_message_value_hash_chain(message_value, previous).map_err(to_js_error)
+}
+
+/// Validate a collection of messages, all by the same author, ordered by ascending sequence
+/// number, with no missing messages.
+///
+/// `previous` is the message which precedes the first message, or `None` if the first message is
+/// the first message of the feed.
+#[wasm_bindgen]
+pub fn validate_message_hash_chain_of_feed_js(
+    messages: Vec<String>,
+    previous: Option<String>,
+) -> Result<(), String> {
+    for (idx, msg) in messages.iter().enumerate() {
+        if idx == 0 {
+            validate_message_hash_chain(msg, previous.as_deref()).map_err(to_js_error)?;
+        } else {
+            validate_message_hash_chain_in_batch(
+                msg.as_bytes(),
+                messages[idx - 1].as_bytes(),
+                &ValidationOptions::default(),
+            )
+            .map_err(to_js_error)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate a collection of out-of-order messages, all by the same author.
+///
+/// See [`validate_ooo_message_hash_chain`].
+#[wasm_bindgen]
+pub fn validate_ooo_message_hash_chain_of_feed_js(messages: Vec<String>) -> Result<(), String> {
+    let mut previous_msg: Option<&str> = None;
+    for msg in messages.iter() {
+        validate_ooo_message_hash_chain(msg, previous_msg).map_err(to_js_error)?;
+        previous_msg = Some(msg);
+    }
+
+    Ok(())
+}
+
+/// Validate a collection of messages which are not required to be in order or to be authored by
+/// a single identity.
+///
+/// See [`validate_multi_author_message_hash_chain`].
+#[wasm_bindgen]
+pub fn validate_multi_author_message_hash_chain_of_feed_js(
+    messages: Vec<String>,
+) -> Result<(), String> {
+    messages
+        .iter()
+        .try_for_each(|msg| validate_multi_author_message_hash_chain(msg).map_err(to_js_error))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_data::{MESSAGE_1, MESSAGE_2, MESSAGE_2_FORK, MESSAGE_3};
+    use crate::wasm::{validate_message_hash_chain_js, validate_message_hash_chain_of_feed_js};
+
+    #[test]
+    fn it_maps_errors_to_strings() {
+        assert!(
+            validate_message_hash_chain_js(MESSAGE_2.as_bytes(), Some(MESSAGE_1.into())).is_ok()
+        );
+        let err = validate_message_hash_chain_js(MESSAGE_2_FORK.as_bytes(), Some(MESSAGE_1.into()))
+            .unwrap_err();
+        assert!(err.contains("fork"));
+    }
+
+    #[test]
+    fn it_validates_a_feed() {
+        let messages = vec![MESSAGE_1.into(), MESSAGE_2.into(), MESSAGE_3.into()];
+        assert!(validate_message_hash_chain_of_feed_js(messages, None).is_ok());
+    }
+}